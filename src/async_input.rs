@@ -0,0 +1,346 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::fmt;
+use std::error;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf};
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use crate::{Instance, Media};
+
+// Size of each chunk read from the async source.
+const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Error of an async media input.
+#[derive(Debug)]
+pub enum AsyncInputError {
+    /// Reading from the source failed.
+    Read(io::Error),
+    /// Seeking the source failed.
+    Seek(io::Error),
+}
+
+impl fmt::Display for AsyncInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AsyncInputError::Read(ref e) => write!(f, "async media input read error: {}", e),
+            AsyncInputError::Seek(ref e) => write!(f, "async media input seek error: {}", e),
+        }
+    }
+}
+
+impl error::Error for AsyncInputError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AsyncInputError::Read(ref e) | AsyncInputError::Seek(ref e) => Some(e),
+        }
+    }
+}
+
+/// Handle to the state of an async media input.
+#[derive(Clone)]
+pub struct AsyncInputHandle {
+    error: Arc<Mutex<Option<AsyncInputError>>>,
+}
+
+impl AsyncInputHandle {
+    /// Takes the last error of the source, if any.
+    /// When the source fails, libvlc also reports MediaPlayerEncounteredError.
+    pub fn take_error(&self) -> Option<AsyncInputError> {
+        self.error.lock().ok().and_then(|mut e| e.take())
+    }
+}
+
+impl Media {
+    /// Create a media reading its data from a non-seekable async `reader`.
+    /// The reader is driven on `handle`, and at most `capacity` chunks are buffered
+    /// ahead of libvlc, so a slow consumer slows down reading from the source.
+    pub fn from_async_read<R>(
+        instance: &Instance, handle: &Handle, reader: R, capacity: usize) -> Option<(Media, AsyncInputHandle)>
+        where R: AsyncRead + Unpin + Send + 'static
+    {
+        let (bridge, input) = AsyncInputBridge::spawn(handle, NoSeek(reader), capacity);
+        Media::from_read(instance, bridge).map(|md| (md, input))
+    }
+
+    /// Create a media reading its data from a seekable async `reader`.
+    /// See from_async_read() for buffering.
+    pub fn from_async_reader<R>(
+        instance: &Instance, handle: &Handle, reader: R, capacity: usize) -> Option<(Media, AsyncInputHandle)>
+        where R: AsyncRead + AsyncSeek + Unpin + Send + 'static
+    {
+        let (bridge, input) = AsyncInputBridge::spawn(handle, reader, capacity);
+        Media::from_reader(instance, bridge).map(|md| (md, input))
+    }
+}
+
+enum Chunk {
+    Data(Vec<u8>),
+    Eof,
+    Error,
+}
+
+// Seek request, answered with the new position and generation.
+struct SeekCommand {
+    pos: SeekFrom,
+    reply: oneshot::Sender<io::Result<(u64, u64)>>,
+}
+
+// Blocking side of an async input, read by libvlc threads.
+// Chunks are tagged with a generation which is increased by each seek,
+// so that data read before a seek is discarded.
+struct AsyncInputBridge {
+    rx: mpsc::Receiver<(u64, Chunk)>,
+    ctrl: mpsc::UnboundedSender<SeekCommand>,
+    generation: u64,
+    pending: Vec<u8>,
+    offset: usize,
+    finished: Option<Chunk>,
+}
+
+impl AsyncInputBridge {
+    fn spawn<R>(handle: &Handle, reader: R, capacity: usize) -> (AsyncInputBridge, AsyncInputHandle)
+        where R: AsyncRead + AsyncSeek + Unpin + Send + 'static
+    {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let (ctrl_tx, ctrl_rx) = mpsc::unbounded_channel();
+        let input = AsyncInputHandle{ error: Arc::new(Mutex::new(None)) };
+
+        handle.spawn(pump(reader, tx, ctrl_rx, input.error.clone()));
+
+        let bridge = AsyncInputBridge {
+            rx,
+            ctrl: ctrl_tx,
+            generation: 0,
+            pending: Vec::new(),
+            offset: 0,
+            finished: None,
+        };
+        (bridge, input)
+    }
+}
+
+impl Read for AsyncInputBridge {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.offset < self.pending.len() {
+                let n = buf.len().min(self.pending.len() - self.offset);
+                buf[..n].copy_from_slice(&self.pending[self.offset..self.offset + n]);
+                self.offset += n;
+                return Ok(n);
+            }
+
+            match self.finished {
+                Some(Chunk::Eof) => return Ok(0),
+                Some(_) => return Err(io::Error::other("async media input failed")),
+                None => (),
+            }
+
+            match self.rx.blocking_recv() {
+                Some((generation, _)) if generation != self.generation => (),
+                Some((_, Chunk::Data(data))) => {
+                    self.pending = data;
+                    self.offset = 0;
+                },
+                Some((_, chunk)) => self.finished = Some(chunk),
+                None => return Err(io::Error::from(io::ErrorKind::BrokenPipe)),
+            }
+        }
+    }
+}
+
+impl Seek for AsyncInputBridge {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.ctrl.send(SeekCommand{ pos, reply: reply_tx })
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        let (pos, generation) = reply_rx.blocking_recv()
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))??;
+
+        self.generation = generation;
+        self.pending.clear();
+        self.offset = 0;
+        self.finished = None;
+        Ok(pos)
+    }
+}
+
+// Reads chunks from the source until the bridge is dropped.
+async fn pump<R>(
+    mut reader: R,
+    tx: mpsc::Sender<(u64, Chunk)>,
+    mut ctrl: mpsc::UnboundedReceiver<SeekCommand>,
+    error: Arc<Mutex<Option<AsyncInputError>>>)
+    where R: AsyncRead + AsyncSeek + Unpin
+{
+    let mut generation = 0;
+    let mut finished = false;
+    let mut buf = vec![0; CHUNK_SIZE];
+
+    loop {
+        let chunk = if finished {
+            match ctrl.recv().await {
+                Some(cmd) => {
+                    seek(&mut reader, cmd, &mut generation, &mut finished, &error).await;
+                    continue;
+                },
+                None => return,
+            }
+        } else {
+            tokio::select! {
+                cmd = ctrl.recv() => match cmd {
+                    Some(cmd) => {
+                        seek(&mut reader, cmd, &mut generation, &mut finished, &error).await;
+                        continue;
+                    },
+                    None => return,
+                },
+                _ = tx.closed() => return,
+                result = reader.read(&mut buf) => match result {
+                    Ok(0) => {
+                        finished = true;
+                        Chunk::Eof
+                    },
+                    Ok(n) => Chunk::Data(buf[..n].to_vec()),
+                    Err(e) => {
+                        finished = true;
+                        set_error(&error, AsyncInputError::Read(e));
+                        Chunk::Error
+                    },
+                },
+            }
+        };
+
+        // Waiting here while the buffer is full gives backpressure to the source.
+        tokio::select! {
+            result = tx.send((generation, chunk)) => if result.is_err() { return },
+            cmd = ctrl.recv() => match cmd {
+                Some(cmd) => seek(&mut reader, cmd, &mut generation, &mut finished, &error).await,
+                None => return,
+            },
+        }
+    }
+}
+
+async fn seek<R: AsyncSeek + Unpin>(
+    reader: &mut R,
+    cmd: SeekCommand,
+    generation: &mut u64,
+    finished: &mut bool,
+    error: &Mutex<Option<AsyncInputError>>)
+{
+    *generation += 1;
+    *finished = false;
+    let result = match reader.seek(cmd.pos).await {
+        Ok(pos) => Ok((pos, *generation)),
+        Err(e) => {
+            let kind = e.kind();
+            set_error(error, AsyncInputError::Seek(e));
+            Err(io::Error::from(kind))
+        },
+    };
+    let _ = cmd.reply.send(result);
+}
+
+fn set_error(error: &Mutex<Option<AsyncInputError>>, e: AsyncInputError) {
+    if let Ok(mut error) = error.lock() {
+        *error = Some(e);
+    }
+}
+
+// Wraps a non-seekable reader for the pump.
+struct NoSeek<R>(R);
+
+impl<R: AsyncRead + Unpin> AsyncRead for NoSeek<R> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl<R: Unpin> AsyncSeek for NoSeek<R> {
+    fn start_seek(self: Pin<&mut Self>, _position: SeekFrom) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "not seekable"))
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<u64>> {
+        Poll::Ready(Err(io::Error::new(io::ErrorKind::Unsupported, "not seekable")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::runtime::Runtime;
+
+    // Serves `data` to the first client, in small writes.
+    async fn serve(data: Vec<u8>) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            for part in data.chunks(1000) {
+                socket.write_all(part).await.unwrap();
+            }
+        });
+        TcpStream::connect(addr).await.unwrap()
+    }
+
+    struct FailingReader;
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(self: Pin<&mut Self>, _cx: &mut Context, _buf: &mut ReadBuf) -> Poll<io::Result<()>> {
+            Poll::Ready(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+        }
+    }
+
+    #[test]
+    fn read_from_local_server() {
+        let rt = Runtime::new().unwrap();
+        let data: Vec<u8> = (0..200_000u32).map(|i| i as u8).collect();
+        let stream = rt.block_on(serve(data.clone()));
+
+        let (mut bridge, input) = AsyncInputBridge::spawn(rt.handle(), NoSeek(stream), 2);
+        let mut received = Vec::new();
+        bridge.read_to_end(&mut received).unwrap();
+
+        assert_eq!(received, data);
+        assert!(input.take_error().is_none());
+    }
+
+    #[test]
+    fn seek_discards_buffered_data() {
+        let rt = Runtime::new().unwrap();
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+
+        let (mut bridge, _) = AsyncInputBridge::spawn(rt.handle(), Cursor::new(data.clone()), 2);
+        let mut buf = [0; 10];
+        bridge.read_exact(&mut buf).unwrap();
+        assert_eq!(bridge.seek(SeekFrom::Start(50_000)).unwrap(), 50_000);
+        bridge.read_exact(&mut buf).unwrap();
+
+        assert_eq!(&buf[..], &data[50_000..50_010]);
+        assert_eq!(bridge.seek(SeekFrom::End(0)).unwrap(), data.len() as u64);
+    }
+
+    #[test]
+    fn read_error_is_reported() {
+        let rt = Runtime::new().unwrap();
+
+        let (mut bridge, input) = AsyncInputBridge::spawn(rt.handle(), NoSeek(FailingReader), 2);
+        let mut buf = [0; 10];
+        assert!(bridge.read(&mut buf).is_err());
+
+        match input.take_error() {
+            Some(AsyncInputError::Read(e)) => assert_eq!(e.kind(), io::ErrorKind::ConnectionReset),
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+}
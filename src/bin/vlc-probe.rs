@@ -0,0 +1,223 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+//! Prints information about media files or MRLs, like ffprobe.
+//!
+//! Exit codes: 0 if all the inputs are playable, 1 if one of them is not, 2 on usage error.
+
+use std::env;
+use std::path::Path;
+use std::process;
+use serde::Serialize;
+use vlc::{Instance, Media, MediaMetadata, MediaStats, MediaTrack, MediaTrackUnion,
+          ParseFlag, ParsedStatus, TrackType};
+
+const USAGE: &str = "\
+usage: vlc-probe [options] <file or MRL>...
+
+options:
+  --json          print a JSON report
+  --timeout <ms>  parsing timeout of each input, 0 waits forever (default: 10000)
+  --network       also parse network inputs
+  -h, --help      print this help";
+
+#[derive(Serialize)]
+struct Report {
+    input: String,
+    mrl: Option<String>,
+    status: Option<ParsedStatus>,
+    playable: bool,
+    /// Duration (in ms).
+    duration: Option<i64>,
+    metadata: MediaMetadata,
+    tracks: Vec<MediaTrack>,
+    stats: Option<MediaStats>,
+    error: Option<String>,
+}
+
+struct Options {
+    json: bool,
+    timeout: i32,
+    flags: Vec<ParseFlag>,
+    inputs: Vec<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options{ json: false, timeout: 10_000, flags: vec![ParseFlag::ParseLocal], inputs: Vec::new() };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--network" => options.flags.push(ParseFlag::ParseNetwork),
+            "--timeout" => {
+                let value = args.next().ok_or("--timeout needs a value")?;
+                options.timeout = value.parse().map_err(|_| format!("invalid timeout: {}", value))?;
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            },
+            "--" => options.inputs.extend(args.by_ref()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ => options.inputs.push(arg),
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("no input".to_owned());
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("vlc-probe: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+
+    let instance = match Instance::with_args(Some(vec!["--quiet".to_owned()])) {
+        Some(instance) => instance,
+        None => {
+            eprintln!("vlc-probe: failed to initialize libvlc");
+            process::exit(2);
+        },
+    };
+
+    let reports: Vec<Report> = options.inputs.iter()
+        .map(|input| probe(&instance, input, &options))
+        .collect();
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    } else {
+        for report in &reports {
+            print_report(report);
+        }
+    }
+
+    if !reports.iter().all(|report| report.playable) {
+        process::exit(1);
+    }
+}
+
+fn probe(instance: &Instance, input: &str, options: &Options) -> Report {
+    let mut report = Report {
+        input: input.to_owned(),
+        mrl: None,
+        status: None,
+        playable: false,
+        duration: None,
+        metadata: MediaMetadata::default(),
+        tracks: Vec::new(),
+        stats: None,
+        error: None,
+    };
+
+    let md = if input.contains("://") {
+        Media::new_location(instance, input)
+    } else if Path::new(input).exists() {
+        Media::new_path(instance, input)
+    } else {
+        report.error = Some("no such file".to_owned());
+        return report;
+    };
+    let md = match md {
+        Some(md) => md,
+        None => {
+            report.error = Some("failed to create media".to_owned());
+            return report;
+        },
+    };
+
+    match md.parse_wait(&options.flags, options.timeout) {
+        Some(status) => report.status = Some(status),
+        None => report.error = Some("failed to parse".to_owned()),
+    }
+
+    report.mrl = md.mrl();
+    report.duration = md.duration();
+    report.metadata = md.metadata();
+    report.tracks = md.tracks().unwrap_or_default();
+    report.stats = md.stats();
+    report.playable = report.status == Some(ParsedStatus::Done) && !report.tracks.is_empty();
+    if report.error.is_none() && !report.playable {
+        report.error = Some(match report.status {
+            Some(ParsedStatus::Done) => "no track found".to_owned(),
+            Some(status) => format!("parsing {:?}", status).to_lowercase(),
+            None => "not parsed".to_owned(),
+        });
+    }
+    report
+}
+
+fn print_report(report: &Report) {
+    println!("{}", report.mrl.as_deref().unwrap_or(&report.input));
+    if let Some(ref error) = report.error {
+        println!("  error: {}", error);
+    }
+    if let Some(duration) = report.duration {
+        println!("  duration: {}", format_time(duration));
+    }
+
+    for (name, value) in &[
+        ("title", report.metadata.title.clone()),
+        ("artist", report.metadata.artist.clone()),
+        ("album", report.metadata.album.clone()),
+        ("genre", report.metadata.genre.clone()),
+        ("date", report.metadata.date.map(|d| d.to_string())),
+        ("track", report.metadata.track_number.map(|n| n.to_string())),
+    ] {
+        if let Some(value) = value {
+            println!("  {}: {}", name, value);
+        }
+    }
+
+    for track in &report.tracks {
+        let mut line = format!("  track {}: {}", track.id, fourcc(track.codec));
+        match track.type_specific_data {
+            MediaTrackUnion::Audio(ref a) => line += &format!(", {} ch, {} Hz", a.channels, a.rate),
+            MediaTrackUnion::Video(ref v) => {
+                line += &format!(", {}x{}", v.width, v.height);
+                if v.frame_rate_den != 0 {
+                    line += &format!(", {:.2} fps", v.frame_rate_num as f64 / v.frame_rate_den as f64);
+                }
+            },
+            MediaTrackUnion::Subtitle(ref s) => if let Some(ref encoding) = s.encoding {
+                line += &format!(", {}", encoding);
+            },
+            MediaTrackUnion::None => (),
+        }
+        if track.bitrate != 0 {
+            line += &format!(", {} kb/s", track.bitrate / 1000);
+        }
+        if let Some(ref language) = track.language {
+            line += &format!(" [{}]", language);
+        }
+        let kind = match track.track_type {
+            TrackType::Audio => "audio",
+            TrackType::Video => "video",
+            TrackType::Text => "subtitle",
+            TrackType::Unknown => "unknown",
+        };
+        println!("{} ({})", line, kind);
+    }
+}
+
+// Formats a time in ms as HH:MM:SS.mmm.
+fn format_time(ms: i64) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+// Codecs are fourcc codes stored in little-endian order.
+fn fourcc(codec: u32) -> String {
+    codec.to_le_bytes().iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '?' })
+        .collect::<String>()
+        .trim_end()
+        .to_owned()
+}
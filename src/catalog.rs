@@ -0,0 +1,451 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::{Instance, Media, MediaList, MediaTrack, MediaTrackUnion, AudioTrack, VideoTrack, SubtitleTrack, MediaRecord};
+use crate::enums::{Meta, TrackType};
+
+// Version of the catalog file format.
+const FORMAT_VERSION: u32 = 1;
+
+/// A media in the catalog.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub mrl: String,
+    #[serde(with = "meta_map")]
+    pub meta: HashMap<Meta, String>,
+    /// Duration (in ms).
+    pub duration: Option<i64>,
+    #[serde(with = "track_list")]
+    pub tracks: Vec<MediaTrack>,
+    pub play_count: u32,
+    /// Position where the playback was left (in ms).
+    pub last_position: Option<i64>,
+}
+
+impl CatalogEntry {
+    /// Create an entry without metadata.
+    pub fn new(mrl: &str) -> CatalogEntry {
+        CatalogEntry {
+            mrl: mrl.to_owned(),
+            meta: HashMap::new(),
+            duration: None,
+            tracks: Vec::new(),
+            play_count: 0,
+            last_position: None,
+        }
+    }
+
+    /// Create an entry from a media, which should be parsed beforehand.
+    pub fn from_media(md: &Media) -> Option<CatalogEntry> {
+        Some(CatalogEntry {
+            meta: md.meta_map(),
+            duration: md.duration(),
+            tracks: md.tracks().unwrap_or_default(),
+            ..CatalogEntry::new(&md.mrl()?)
+        })
+    }
+
+    /// Create an entry from a record of the Scanner.
+    pub fn from_record(record: &MediaRecord) -> Option<CatalogEntry> {
+        Some(CatalogEntry {
+            meta: record.meta.clone(),
+            duration: record.duration,
+            tracks: record.tracks.clone(),
+            ..CatalogEntry::new(record.mrl.as_ref()?)
+        })
+    }
+
+    /// Returns a meta of the entry.
+    pub fn meta(&self, meta: Meta) -> Option<&str> {
+        self.meta.get(&meta).map(|s| s.as_str())
+    }
+
+    /// Create a media for this entry.
+    pub fn to_media(&self, instance: &Instance) -> Option<Media> {
+        Media::new_location(instance, &self.mrl)
+    }
+}
+
+/// Error of a catalog operation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CatalogError {
+    /// The media has no MRL.
+    NoMrl,
+    /// No entry has this MRL.
+    NotFound(String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CatalogError::NoMrl => write!(f, "media has no MRL"),
+            CatalogError::NotFound(ref mrl) => write!(f, "no catalog entry for {}", mrl),
+        }
+    }
+}
+
+impl error::Error for CatalogError {}
+
+#[derive(Serialize, Deserialize)]
+struct CatalogFile {
+    version: u32,
+    entries: Vec<CatalogEntry>,
+}
+
+/// A persistent media catalog, stored as a JSON file.
+///
+/// Entries are identified by their MRL. Changes are written by save().
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    path: Option<PathBuf>,
+    entries: Vec<CatalogEntry>,
+    index: HashMap<String, usize>,
+}
+
+impl Catalog {
+    /// Create an empty catalog which is not stored.
+    pub fn new() -> Catalog {
+        Catalog{ path: None, entries: Vec::new(), index: HashMap::new() }
+    }
+
+    /// Open the catalog stored at `path`, or create an empty one if the file does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Catalog> {
+        let path = path.as_ref();
+        let mut catalog = Catalog::new();
+        catalog.path = Some(path.to_owned());
+
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(catalog),
+            Err(e) => return Err(e),
+        };
+        let file: CatalogFile = serde_json::from_slice(&data)?;
+        if file.version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported catalog version {}", file.version)));
+        }
+        for entry in file.entries {
+            catalog.insert(entry);
+        }
+        Ok(catalog)
+    }
+
+    /// Write the catalog to its file.
+    /// The file is replaced atomically, so it is never left half-written.
+    pub fn save(&self) -> io::Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let file = CatalogFile{ version: FORMAT_VERSION, entries: self.entries.clone() };
+        let data = serde_json::to_vec_pretty(&file)?;
+
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Returns the path of the catalog file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Add an entry, replacing the entry with the same MRL.
+    /// The play count and last position of a replaced entry are kept.
+    pub fn insert(&mut self, mut entry: CatalogEntry) {
+        match self.index.get(&entry.mrl) {
+            Some(&i) => {
+                entry.play_count = self.entries[i].play_count;
+                entry.last_position = self.entries[i].last_position;
+                self.entries[i] = entry;
+            },
+            None => {
+                self.index.insert(entry.mrl.clone(), self.entries.len());
+                self.entries.push(entry);
+            },
+        }
+    }
+
+    /// Add a media, which should be parsed beforehand.
+    pub fn add_media(&mut self, md: &Media) -> Result<(), CatalogError> {
+        let entry = CatalogEntry::from_media(md).ok_or(CatalogError::NoMrl)?;
+        self.insert(entry);
+        Ok(())
+    }
+
+    /// Add all the media of a media list, e.g. the MediaLibrary::media_list().
+    pub fn add_media_list(&mut self, list: &MediaList) {
+        list.lock();
+        let items: Vec<Media> = (0..list.count()).filter_map(|i| list.item_at_index(i)).collect();
+        list.unlock();
+
+        for md in items {
+            let _ = self.add_media(&md);
+        }
+    }
+
+    /// Remove the entry with this MRL.
+    pub fn remove(&mut self, mrl: &str) -> Option<CatalogEntry> {
+        let i = self.index.remove(mrl)?;
+        let entry = self.entries.remove(i);
+        for j in self.index.values_mut() {
+            if *j > i { *j -= 1; }
+        }
+        Some(entry)
+    }
+
+    /// Returns the entry with this MRL.
+    pub fn get(&self, mrl: &str) -> Option<&CatalogEntry> {
+        self.index.get(mrl).map(|&i| &self.entries[i])
+    }
+
+    /// Returns all the entries, in insertion order.
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Count a playback of the entry with this MRL.
+    pub fn record_play(&mut self, mrl: &str) -> Result<(), CatalogError> {
+        let i = self.position(mrl)?;
+        self.entries[i].play_count += 1;
+        Ok(())
+    }
+
+    /// Set the position where the playback of the entry was left (in ms).
+    pub fn set_last_position(&mut self, mrl: &str, position: Option<i64>) -> Result<(), CatalogError> {
+        let i = self.position(mrl)?;
+        self.entries[i].last_position = position;
+        Ok(())
+    }
+
+    fn position(&self, mrl: &str) -> Result<usize, CatalogError> {
+        self.index.get(mrl).copied().ok_or_else(|| CatalogError::NotFound(mrl.to_owned()))
+    }
+
+    /// Returns the entries matching the query, in insertion order.
+    pub fn search(&self, query: &CatalogQuery) -> Vec<&CatalogEntry> {
+        self.entries.iter().filter(|entry| query.matches(entry)).collect()
+    }
+
+    /// Create a media list of the entries.
+    pub fn to_media_list(instance: &Instance, entries: &[&CatalogEntry]) -> Option<MediaList> {
+        let list = MediaList::new(instance)?;
+        list.lock();
+        for entry in entries {
+            if let Some(md) = entry.to_media(instance) {
+                let _ = list.add_media(&md);
+            }
+        }
+        list.unlock();
+        Some(list)
+    }
+}
+
+impl Default for Catalog {
+    fn default() -> Catalog {
+        Catalog::new()
+    }
+}
+
+/// Query of Catalog::search(). All the conditions which are set must match.
+/// Texts are compared case-insensitively.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct CatalogQuery {
+    text: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    genre: Option<String>,
+    duration: Option<RangeInclusive<i64>>,
+}
+
+impl CatalogQuery {
+    pub fn new() -> CatalogQuery {
+        CatalogQuery::default()
+    }
+
+    /// Match entries whose MRL or any meta contains `text`.
+    pub fn text(mut self, text: &str) -> CatalogQuery {
+        self.text = Some(text.to_lowercase());
+        self
+    }
+
+    /// Match entries of this artist.
+    pub fn artist(mut self, artist: &str) -> CatalogQuery {
+        self.artist = Some(artist.to_lowercase());
+        self
+    }
+
+    /// Match entries of this album.
+    pub fn album(mut self, album: &str) -> CatalogQuery {
+        self.album = Some(album.to_lowercase());
+        self
+    }
+
+    /// Match entries of this genre.
+    pub fn genre(mut self, genre: &str) -> CatalogQuery {
+        self.genre = Some(genre.to_lowercase());
+        self
+    }
+
+    /// Match entries whose duration (in ms) is in the range.
+    pub fn duration(mut self, range: RangeInclusive<i64>) -> CatalogQuery {
+        self.duration = Some(range);
+        self
+    }
+
+    /// Returns true if the entry matches the query.
+    pub fn matches(&self, entry: &CatalogEntry) -> bool {
+        let meta_is = |meta: Meta, value: &Option<String>| match *value {
+            Some(ref value) => entry.meta(meta).is_some_and(|v| v.to_lowercase() == *value),
+            None => true,
+        };
+
+        if let Some(ref text) = self.text {
+            let found = entry.mrl.to_lowercase().contains(text.as_str())
+                || entry.meta.values().any(|v| v.to_lowercase().contains(text.as_str()));
+            if !found {
+                return false;
+            }
+        }
+        if let Some(ref range) = self.duration {
+            if !entry.duration.is_some_and(|d| range.contains(&d)) {
+                return false;
+            }
+        }
+        meta_is(Meta::Artist, &self.artist)
+            && meta_is(Meta::Album, &self.album)
+            && meta_is(Meta::Genre, &self.genre)
+    }
+}
+
+// The file format does not depend on the serde feature of the crate,
+// so the libvlc types are stored through these definitions.
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Meta")]
+#[allow(clippy::upper_case_acronyms)]
+enum MetaDef {
+    Title, Artist, Genre, Copyright, Album, TrackNumber, Description, Rating, Date, Setting,
+    URL, Language, NowPlaying, Publisher, EncodedBy, ArtworkURL, TrackID, TrackTotal, Director,
+    Season, Episode, ShowName, Actors,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "TrackType")]
+enum TrackTypeDef {
+    Unknown, Audio, Video, Text,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "MediaTrack")]
+struct MediaTrackDef {
+    codec: u32,
+    original_fourcc: u32,
+    id: i32,
+    #[serde(with = "TrackTypeDef")]
+    track_type: TrackType,
+    profile: i32,
+    level: i32,
+    bitrate: u32,
+    language: Option<String>,
+    description: Option<String>,
+    #[serde(with = "MediaTrackUnionDef")]
+    type_specific_data: MediaTrackUnion,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "MediaTrackUnion")]
+enum MediaTrackUnionDef {
+    Audio(#[serde(with = "AudioTrackDef")] AudioTrack),
+    Video(#[serde(with = "VideoTrackDef")] VideoTrack),
+    Subtitle(#[serde(with = "SubtitleTrackDef")] SubtitleTrack),
+    None,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "AudioTrack")]
+struct AudioTrackDef {
+    channels: u32,
+    rate: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "VideoTrack")]
+struct VideoTrackDef {
+    height: u32,
+    width: u32,
+    sar_num: u32,
+    sar_den: u32,
+    frame_rate_num: u32,
+    frame_rate_den: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SubtitleTrack")]
+struct SubtitleTrackDef {
+    encoding: Option<String>,
+}
+
+mod meta_map {
+    use std::collections::HashMap;
+    use serde::{Serialize, Deserialize, Serializer, Deserializer};
+    use crate::enums::Meta;
+    use super::MetaDef;
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
+    struct Key(#[serde(with = "MetaDef")] Meta);
+
+    pub fn serialize<S: Serializer>(meta: &HashMap<Meta, String>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(meta.iter().map(|(&k, v)| (Key(k), v)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<Meta, String>, D::Error> {
+        let meta = HashMap::<Key, String>::deserialize(deserializer)?;
+        Ok(meta.into_iter().map(|(k, v)| (k.0, v)).collect())
+    }
+}
+
+mod track_list {
+    use serde::{Serialize, Deserialize, Serializer, Deserializer};
+    use crate::MediaTrack;
+    use super::MediaTrackDef;
+
+    struct TrackRef<'a>(&'a MediaTrack);
+
+    impl Serialize for TrackRef<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            MediaTrackDef::serialize(self.0, serializer)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Track(#[serde(with = "MediaTrackDef")] MediaTrack);
+
+    pub fn serialize<S: Serializer>(tracks: &[MediaTrack], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(tracks.iter().map(TrackRef))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<MediaTrack>, D::Error> {
+        let tracks = Vec::<Track>::deserialize(deserializer)?;
+        Ok(tracks.into_iter().map(|track| track.0).collect())
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::{Instance, Media, MediaOptions};
+use crate::sout::Sout;
+use crate::transcoder::{Transcoder, TranscodePreset, TranscodeHandle, TranscodeCanceller, TranscodeError, TranscodeOutput};
+
+/// How the clip is written.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ClipMode {
+    /// Copy the streams without re-encoding. The cut happens on key frames.
+    Copy,
+    /// Re-encode with a chain, see Transcoder::new().
+    Reencode(Sout),
+    /// Re-encode with a preset.
+    Preset(TranscodePreset),
+}
+
+/// Error of a clip export.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ClipError {
+    /// The stop time is not after the start time.
+    InvalidRange,
+    Transcode(TranscodeError),
+    /// The duration of the output could not be read.
+    UnknownDuration,
+    /// The duration of the output (in ms) differs from the range by more than the tolerance.
+    Duration { expected: i64, actual: i64 },
+}
+
+impl fmt::Display for ClipError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClipError::InvalidRange => write!(f, "invalid clip range"),
+            ClipError::Transcode(ref e) => e.fmt(f),
+            ClipError::UnknownDuration => write!(f, "cannot read the duration of the clip"),
+            ClipError::Duration { expected, actual } =>
+                write!(f, "clip duration is {} ms instead of {} ms", actual, expected),
+        }
+    }
+}
+
+impl error::Error for ClipError {}
+
+impl From<TranscodeError> for ClipError {
+    fn from(e: TranscodeError) -> ClipError {
+        ClipError::Transcode(e)
+    }
+}
+
+/// Result of a successful clip export.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ClipOutput {
+    pub output: TranscodeOutput,
+    /// Duration of the written clip (in ms).
+    pub duration: i64,
+}
+
+/// Progress of a clip export.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClipProgress {
+    /// Part of the range written, between 0.0 and 1.0.
+    pub fraction: f32,
+    /// Time in the input (in ms).
+    pub time: Option<i64>,
+}
+
+/// Exports a time range of a media to a file, with the start-time and stop-time options.
+#[derive(Clone)]
+pub struct ClipExporter {
+    instance: Instance,
+    media: Media,
+    start: i64,
+    stop: i64,
+    output: PathBuf,
+    mode: ClipMode,
+    tolerance: i64,
+}
+
+impl ClipExporter {
+    /// Create an exporter of the range between start and stop (in ms), copying the streams.
+    /// The default tolerance of the duration check is 1 second.
+    pub fn new<P: AsRef<Path>>(instance: &Instance, media: &Media, start: i64, stop: i64, output: P) -> ClipExporter {
+        ClipExporter {
+            instance: instance.clone(),
+            media: media.clone(),
+            start,
+            stop,
+            output: output.as_ref().to_path_buf(),
+            mode: ClipMode::Copy,
+            tolerance: 1000,
+        }
+    }
+
+    pub fn mode(mut self, mode: ClipMode) -> ClipExporter {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the accepted difference (in ms) between the duration of the output and the range.
+    pub fn tolerance(mut self, tolerance: i64) -> ClipExporter {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Returns the transcoder which writes the clip.
+    pub fn transcoder(&self) -> Transcoder {
+        let transcoder = match self.mode {
+            ClipMode::Copy => Transcoder::new(&self.instance, &self.media, Sout::new(), &self.output),
+            ClipMode::Reencode(ref profile) => Transcoder::new(&self.instance, &self.media, profile.clone(), &self.output),
+            ClipMode::Preset(preset) => Transcoder::with_preset(&self.instance, &self.media, preset, &self.output),
+        };
+        transcoder.options(MediaOptions::new()
+            .start_time(self.start as f64 / 1000.0)
+            .stop_time(self.stop as f64 / 1000.0))
+    }
+
+    /// Start the export. The returned handle yields the progress.
+    pub fn start(&self) -> Result<ClipHandle, ClipError> {
+        check_range(self.start, self.stop)?;
+        Ok(ClipHandle {
+            handle: self.transcoder().start()?,
+            instance: self.instance.clone(),
+            start: self.start,
+            stop: self.stop,
+            tolerance: self.tolerance,
+        })
+    }
+
+    /// Export and wait for the end.
+    pub fn run(&self) -> Result<ClipOutput, ClipError> {
+        self.start()?.wait()
+    }
+}
+
+/// Handle to a running clip export. Iterating over it yields the progress until the end.
+/// Dropping it cancels the export.
+pub struct ClipHandle {
+    handle: TranscodeHandle,
+    instance: Instance,
+    start: i64,
+    stop: i64,
+    tolerance: i64,
+}
+
+impl ClipHandle {
+    /// Returns a canceller which can be used from another thread.
+    pub fn canceller(&self) -> TranscodeCanceller {
+        self.handle.canceller()
+    }
+
+    pub fn cancel(&self) {
+        self.handle.cancel();
+    }
+
+    /// Wait for the end, then check the duration of the output.
+    pub fn wait(self) -> Result<ClipOutput, ClipError> {
+        let output = self.handle.wait()?;
+
+        let md = Media::new_path(&self.instance, &output.path).ok_or(ClipError::UnknownDuration)?;
+        md.parse();
+        let duration = md.duration().filter(|&duration| duration > 0).ok_or(ClipError::UnknownDuration)?;
+
+        let expected = self.stop - self.start;
+        if (duration - expected).abs() > self.tolerance {
+            return Err(ClipError::Duration { expected, actual: duration });
+        }
+        Ok(ClipOutput { output, duration })
+    }
+}
+
+impl Iterator for ClipHandle {
+    type Item = ClipProgress;
+
+    fn next(&mut self) -> Option<ClipProgress> {
+        let progress = self.handle.next()?;
+        let fraction = progress.time.map_or(0.0, |time| range_fraction(time, self.start, self.stop));
+        Some(ClipProgress { fraction, time: progress.time })
+    }
+}
+
+fn check_range(start: i64, stop: i64) -> Result<(), ClipError> {
+    if start < 0 || stop <= start {
+        return Err(ClipError::InvalidRange);
+    }
+    Ok(())
+}
+
+// Part of the range between start and stop before the input time, between 0.0 and 1.0.
+fn range_fraction(time: i64, start: i64, stop: i64) -> f32 {
+    ((time - start) as f32 / (stop - start) as f32).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range() {
+        assert_eq!(check_range(0, 1), Ok(()));
+        assert_eq!(check_range(1500, 10_000), Ok(()));
+        assert_eq!(check_range(5000, 5000), Err(ClipError::InvalidRange));
+        assert_eq!(check_range(5000, 4000), Err(ClipError::InvalidRange));
+        assert_eq!(check_range(-1, 1000), Err(ClipError::InvalidRange));
+    }
+
+    #[test]
+    fn fraction() {
+        assert_eq!(range_fraction(1000, 1000, 3000), 0.0);
+        assert_eq!(range_fraction(2000, 1000, 3000), 0.5);
+        assert_eq!(range_fraction(3000, 1000, 3000), 1.0);
+        // The input may be read before the start and after the stop.
+        assert_eq!(range_fraction(0, 1000, 3000), 0.0);
+        assert_eq!(range_fraction(4000, 1000, 3000), 1.0);
+    }
+}
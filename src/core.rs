@@ -7,10 +7,12 @@ use std::borrow::Cow;
 use std::marker::PhantomData;
 use std::ffi::{CString, CStr};
 use std::i32;
+use std::sync::Arc;
 use libc::{c_void, c_char, c_int};
 use crate::sys;
 use crate::tools::{to_cstr, from_cstr, from_cstr_ref};
 use crate::enums::*;
+use crate::vlm::VlmNames;
 
 /// Retrieve libvlc version.
 pub fn version() -> String {
@@ -26,13 +28,21 @@ pub fn compiler() -> String {
     }
 }
 
+/// A libvlc instance.
+///
+/// Every object created from an instance (Media, MediaPlayer, MediaList, ...) holds its own
+/// reference to the libvlc instance, so it stays valid after the Instance is dropped.
+/// The instance is thread-safe, so it is Send and Sync.
 pub struct Instance {
     pub(crate) ptr: *mut sys::libvlc_instance_t,
-
+    // Shared by the clones, and freed with the last one.
+    pub(crate) vlm_names: Arc<VlmNames>,
 }
 
 unsafe impl Send for Instance {}
 
+unsafe impl Sync for Instance {}
+
 impl Instance {
     /// Create and initialize a libvlc instance with specified args.
     /// Note: args.len() has to be less or equal to i32::MAX
@@ -60,7 +70,7 @@ impl Instance {
                 return None;
             }
 
-            Some(Instance{ptr: p})
+            Some(Instance{ptr: p, vlm_names: Arc::new(VlmNames::new(p))})
         }
     }
 
@@ -145,6 +155,14 @@ impl Instance {
     }
 }
 
+impl Clone for Instance {
+    /// Increments the reference count of the libvlc instance.
+    fn clone(&self) -> Instance {
+        unsafe{ sys::libvlc_retain(self.ptr) };
+        Instance{ptr: self.ptr, vlm_names: self.vlm_names.clone()}
+    }
+}
+
 impl Drop for Instance {
     fn drop(&mut self) {
         unsafe{
@@ -203,6 +221,7 @@ pub struct ModuleDescriptionListIter<'a> {
 /// Description of a module.
 /// The strings are owned.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModuleDescription {
     pub name:      Option<String>,
     pub shortname: Option<String>,
@@ -260,11 +279,13 @@ pub fn clearerr() {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Event {
     MediaMetaChanged(Meta),
     MediaSubItemAdded,
     MediaDurationChanged(i64),
-    MediaParsedChanged(i32),
+    /// None if libvlc sent an unknown status.
+    MediaParsedChanged(Option<ParsedStatus>),
     MediaFreed,
     MediaStateChanged(State),
     MediaSubItemTreeAdded,
@@ -307,19 +328,56 @@ pub enum Event {
     MediaDiscovererStarted,
     MediaDiscovererEnded,
 
+    // The VLM events carry the instance name and the media name.
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaAdded(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaRemoved(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaChanged(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStarted(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStopped(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusInit(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusOpening(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusPlaying(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusPause(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusEnd(Option<String>, Option<String>),
+    #[cfg_attr(feature = "serde", serde(with = "vlm_event_names"))]
     VlmMediaInstanceStatusError(Option<String>, Option<String>)
 }
 
+// Serializes the names of a VLM event as named fields, which are part of SERDE_VERSION.
+#[cfg(feature = "serde")]
+mod vlm_event_names {
+    use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct VlmEventNames {
+        #[serde(rename = "instance_name")]
+        instance: Option<String>,
+        #[serde(rename = "media_name")]
+        media: Option<String>,
+    }
+
+    pub fn serialize<S: Serializer>(instance: &Option<String>, media: &Option<String>, serializer: S)
+                                    -> Result<S::Ok, S::Error> {
+        VlmEventNames{ instance: instance.clone(), media: media.clone() }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D)
+                                                  -> Result<(Option<String>, Option<String>), D::Error> {
+        let names = VlmEventNames::deserialize(deserializer)?;
+        Ok((names.instance, names.media))
+    }
+}
+
 pub struct EventManager<'a> {
     pub(crate) ptr: *mut sys::libvlc_event_manager_t,
     pub(crate) _phantomdata: ::std::marker::PhantomData<&'a sys::libvlc_event_manager_t>,
@@ -373,7 +431,7 @@ unsafe extern "C" fn event_manager_callback(pe: *const sys::libvlc_event_t, data
 }
 
 // Convert c-style libvlc_event_t to Event
-fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
+pub(crate) fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
     let event_type: EventType = unsafe{ ::std::mem::transmute((*pe)._type) };
 
     match event_type {
@@ -392,7 +450,7 @@ fn conv_event(pe: *const sys::libvlc_event_t) -> Event {
         },
         EventType::MediaParsedChanged => {
             unsafe{
-                Event::MediaParsedChanged((*pe).u.media_parsed_changed.new_status)
+                Event::MediaParsedChanged(crate::media::parsed_status_from((*pe).u.media_parsed_changed.new_status))
             }
         },
         EventType::MediaFreed => {
@@ -4,6 +4,7 @@
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LogLevel {
     Debug = 0,
     Notice = 2,
@@ -13,6 +14,7 @@ pub enum LogLevel {
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Meta {
     Title,
     Artist,
@@ -41,6 +43,7 @@ pub enum Meta {
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum State {
     NothingSpecial = 0,
     Opening,
@@ -54,6 +57,7 @@ pub enum State {
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TrackType {
     Unknown = -1,
     Audio   = 0,
@@ -63,6 +67,7 @@ pub enum TrackType {
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Position {
     Disable = -1,
     Center,
@@ -87,17 +92,49 @@ pub enum VideoAdjustOption {
     Gamma
 }
 
-// #[repr(C)]
-// #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-// pub enum ParseFlag {
-//     ParseLocal,
-//     ParseNetwork,
-//     FetchLocal,
-//     FetchNetwork,
-// }
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MediaDiscovererCategory {
+    Devices = 0,
+    Lan,
+    Podcasts,
+    LocalDirs,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlaybackMode {
+    Default = 0,
+    Loop,
+    Repeat,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseFlag {
+    ParseLocal   = 0x00,
+    ParseNetwork = 0x01,
+    FetchLocal   = 0x02,
+    FetchNetwork = 0x04,
+    DoInteract   = 0x08,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParsedStatus {
+    Skipped = 1,
+    Failed,
+    Timeout,
+    Done,
+}
 
 #[repr(C)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventType {
     MediaMetaChanged = 0,
     MediaSubItemAdded,
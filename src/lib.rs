@@ -9,20 +9,65 @@ pub mod sys;
 mod tools;
 mod core;
 mod media;
+mod metadata;
 mod media_player;
 mod media_list;
+mod media_list_player;
 mod media_library;
+mod media_discoverer;
 mod enums;
 mod video;
 mod audio;
+mod sout;
+mod transcoder;
+mod clip;
 mod vlm;
+mod vlm_batch;
+mod vlm_config;
+mod vlm_monitor;
+mod vlm_supervisor;
+mod scanner;
+#[cfg(feature = "catalog")]
+mod catalog;
+#[cfg(feature = "scheduler")]
+mod scheduler;
+#[cfg(feature = "tokio")]
+mod async_input;
 
 pub use crate::enums::*;
 pub use crate::core::*;
 pub use crate::media::*;
+pub use crate::metadata::*;
 pub use crate::media_player::*;
 pub use crate::media_list::*;
+pub use crate::media_list_player::*;
 pub use crate::media_library::*;
+pub use crate::media_discoverer::*;
 pub use crate::video::*;
 pub use crate::audio::*;
+pub use crate::sout::*;
+pub use crate::transcoder::*;
+pub use crate::clip::*;
 pub use crate::vlm::*;
+pub use crate::vlm_batch::*;
+pub use crate::vlm_config::*;
+pub use crate::vlm_monitor::*;
+pub use crate::vlm_supervisor::*;
+pub use crate::scanner::*;
+#[cfg(feature = "catalog")]
+pub use crate::catalog::*;
+#[cfg(feature = "scheduler")]
+pub use crate::scheduler::*;
+#[cfg(feature = "tokio")]
+pub use crate::async_input::*;
+
+/// Version of the serialized form of the data types, with the `serde` feature.
+///
+/// The serialized data does not carry this version. Store it alongside the data
+/// (e.g. in a "serde_version" field of the enclosing document) and compare it when reading back.
+/// It is incremented when a field or a variant is renamed or removed, or when its type changes.
+///
+/// In version 1, the names are those of the Rust fields and variants,
+/// and the VLM events have "instance_name" and "media_name" fields.
+#[cfg(feature = "serde")]
+pub const SERDE_VERSION: u32 = 1;
@@ -3,17 +3,32 @@
 // Licensed under the MIT license, see the LICENSE file.
 
 use crate::sys;
-use crate::{Instance, EventManager};
-use crate::enums::{State, Meta, TrackType};
+use crate::{Instance, EventManager, MediaList};
+use crate::enums::{State, Meta, TrackType, EventType, ParseFlag, ParsedStatus};
 use crate::tools::{to_cstr, from_cstr, path_to_cstr};
 use std::path::Path;
-
+use std::io::{self, Read, Seek, SeekFrom};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::time::{Duration, Instant};
+use std::any::Any;
+use std::collections::HashMap;
+use libc::{c_void, c_int, c_uchar, ssize_t};
+
+/// A media descriptor.
+///
+/// It holds a reference to the libvlc instance it was created from.
+/// libvlc locks the media internally, so it is Send and Sync.
 pub struct Media {
     pub(crate) ptr: *mut sys::libvlc_media_t,
 }
 
 unsafe impl Send for Media {}
 
+unsafe impl Sync for Media {}
+
 impl Media {
     /// Create a media with a certain given media resource location, for instance a valid URL.
     pub fn new_location(instance: &Instance, mrl: &str) -> Option<Media> {
@@ -57,6 +72,165 @@ impl Media {
         }
     }
 
+    /// Create a media as an empty node with a given name.
+    /// Subitems can be added to the list returned by subitems() to build a tree.
+    pub fn new_node(instance: &Instance, name: &str) -> Option<Media> {
+        let cstr = to_cstr(name);
+
+        unsafe{
+            let p = sys::libvlc_media_new_as_node(instance.ptr, cstr.as_ptr());
+            if p.is_null() {
+                return None;
+            }
+
+            Some(Media{ptr: p})
+        }
+    }
+
+    /// Create a media reading its data from `reader`.
+    /// The reader is rewound to the start each time the media is opened,
+    /// and is dropped when the underlying media is freed.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(instance: &Instance, reader: R) -> Option<Media> {
+        Media::new_callbacks(instance, MediaInput::Seekable(Box::new(reader)))
+    }
+
+    /// Create a media reading its data from a non-seekable `reader`.
+    /// The data is consumed as it is played, so the media can only be played once.
+    pub fn from_read<R: Read + Send + 'static>(instance: &Instance, reader: R) -> Option<Media> {
+        Media::new_callbacks(instance, MediaInput::Stream(Box::new(reader)))
+    }
+
+    fn new_callbacks(instance: &Instance, input: MediaInput) -> Option<Media> {
+        let seekable = input.is_seekable();
+        let data = Box::into_raw(Box::new(Mutex::new(input)));
+
+        unsafe{
+            let p = sys::libvlc_media_new_callbacks(
+                instance.ptr,
+                Some(media_input_open),
+                Some(media_input_read),
+                if seekable {Some(media_input_seek)} else {None},
+                Some(media_input_close),
+                data as *mut c_void);
+            if p.is_null() {
+                drop(Box::from_raw(data));
+                return None;
+            }
+
+            // The input data must live as long as libvlc may read from it,
+            // so it is freed along with the media rather than with this wrapper.
+            let em = sys::libvlc_media_event_manager(p);
+            if sys::libvlc_event_attach(
+                em, EventType::MediaFreed as i32, media_input_free, data as *mut c_void) != 0 {
+                sys::libvlc_media_release(p);
+                drop(Box::from_raw(data));
+                return None;
+            }
+
+            let md = Media{ptr: p};
+            md.data().callbacks.store(true, Ordering::SeqCst);
+            Some(md)
+        }
+    }
+
+    /// Add an option to the media.
+    /// The option is trusted and unique, see add_option_flag().
+    pub fn add_option(&self, option: &str) {
+        self.add_option_flag(option, true, true);
+    }
+
+    /// Add an option to the media with configurable flags.
+    /// A trusted option may set options which are unsafe for untrusted input (e.g. sout),
+    /// and a unique option is not added again if the same option is already set.
+    pub fn add_option_flag(&self, option: &str, trusted: bool, unique: bool) {
+        let mut flags = 0;
+        if trusted { flags |= sys::libvlc_media_option_trusted; }
+        if unique { flags |= sys::libvlc_media_option_unique; }
+
+        let mut options = self.data().options.lock().unwrap();
+        unsafe{ sys::libvlc_media_add_option_flag(self.ptr, to_cstr(option).as_ptr(), flags) };
+        if !unique || !options.iter().any(|o| o.option == option) {
+            options.push(MediaOption{ option: option.to_owned(), trusted, unique });
+        }
+    }
+
+    /// Returns the options added to this media object, in the order they were added.
+    /// A copy made by duplicate() starts with the options of the original.
+    pub fn applied_options(&self) -> Vec<MediaOption> {
+        self.data().options.lock().unwrap().clone()
+    }
+
+    /// Add all the options of the builder to the media.
+    pub fn add_options(&self, options: &MediaOptions) {
+        for option in options.options() {
+            self.add_option_flag(&option.option, option.trusted, option.unique);
+        }
+    }
+
+    /// Duplicate a media descriptor object.
+    /// The copy shares the underlying input item, so options and meta added to the copy
+    /// are also added to the original, and the other way around.
+    /// The user data is not copied.
+    ///
+    /// Returns None for a media created with from_reader() or from_read(),
+    /// since the copy would outlive the reader.
+    pub fn duplicate(&self) -> Option<Media> {
+        if self.has_callbacks() {
+            return None;
+        }
+
+        unsafe{
+            let p = sys::libvlc_media_duplicate(self.ptr);
+            if p.is_null() {
+                return None;
+            }
+
+            let md = Media{ptr: p};
+            *md.data().options.lock().unwrap() = self.applied_options();
+            Some(md)
+        }
+    }
+
+    // Whether the media reads its data from callbacks, see from_reader() and from_read().
+    pub(crate) fn has_callbacks(&self) -> bool {
+        self.data().callbacks.load(Ordering::SeqCst)
+    }
+
+    /// Store a value in the user data slot of the media, replacing the previous one.
+    /// The value is dropped when it is replaced or when the media is freed.
+    pub fn set_user_data<T: Any + Send + Sync>(&self, data: T) {
+        *self.data().user_data.lock().unwrap() = Some(Arc::new(data));
+    }
+
+    /// Get the value of the user data slot, if it is set and of type T.
+    pub fn user_data<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let data = self.data().user_data.lock().unwrap().clone();
+        data.and_then(|data| data.downcast().ok())
+    }
+
+    /// Clear the user data slot, dropping its value if no other reference to it is held.
+    pub fn clear_user_data(&self) {
+        *self.data().user_data.lock().unwrap() = None;
+    }
+
+    // Returns the data attached to the media, creating it on first use.
+    fn data(&self) -> &MediaData {
+        let _guard = MEDIA_DATA_LOCK.lock().unwrap();
+
+        unsafe{
+            let mut p = sys::libvlc_media_get_user_data(self.ptr) as *mut MediaData;
+            if p.is_null() {
+                p = Box::into_raw(Box::default());
+                let em = sys::libvlc_media_event_manager(self.ptr);
+                // Without the handler the data would leak, which is still safe.
+                sys::libvlc_event_attach(
+                    em, EventType::MediaFreed as i32, media_data_free, p as *mut c_void);
+                sys::libvlc_media_set_user_data(self.ptr, p as *mut c_void);
+            }
+            &*p
+        }
+    }
+
     pub fn mrl(&self) -> Option<String> {
         unsafe{
             let p_str = sys::libvlc_media_get_mrl(self.ptr);
@@ -85,6 +259,11 @@ impl Media {
         }
     }
 
+    // Returns all the meta set on the media.
+    pub(crate) fn meta_map(&self) -> HashMap<Meta, String> {
+        ALL_META.iter().filter_map(|&m| self.get_meta(m).map(|value| (m, value))).collect()
+    }
+
     /// Set the meta of the media.
     /// (This function will not save the meta, call save_meta in order to save the meta)
     pub fn set_meta(&self, meta: Meta, value: &str) {
@@ -126,6 +305,103 @@ impl Media {
         if unsafe{ sys::libvlc_media_is_parsed(self.ptr) } == 0 { false }else{ true }
     }
 
+    /// Parse the media asynchronously with options.
+    /// The end of parsing is notified by the MediaParsedChanged event.
+    /// `timeout` is in ms; -1 uses the default timeout and 0 waits forever.
+    /// Without any flag, only local files are parsed (ParseFlag::ParseLocal).
+    /// Returns false if the parsing could not be started.
+    pub fn parse_with_options(&self, flags: &[ParseFlag], timeout: i32) -> bool {
+        let flags = flags.iter().fold(0, |acc, &flag| acc | flag as i32);
+
+        unsafe{ sys::libvlc_media_parse_with_options(self.ptr, flags, timeout) == 0 }
+    }
+
+    /// Parse the media with options, see parse_with_options(), and wait for the end of parsing.
+    /// libvlc parses a media only once, so the status of a previous parsing is returned if there is one.
+    /// Unless `timeout` is 0, the wait is bounded by the timeout, after which ParsedStatus::Timeout is returned.
+    /// Returns None if the parsing could not be started or libvlc sent an unknown status.
+    pub fn parse_wait(&self, flags: &[ParseFlag], timeout: i32) -> Option<ParsedStatus> {
+        self.parse_until(flags, timeout, &AtomicBool::new(false))
+    }
+
+    // Like parse_wait(), but stops the parsing and returns None once cancelled is set.
+    pub(crate) fn parse_until(&self, flags: &[ParseFlag], timeout: i32, cancelled: &AtomicBool) -> Option<ParsedStatus> {
+        // A new request is ignored once the media was parsed, and no event is sent.
+        if let Some(status) = self.parsed_status() {
+            return Some(status);
+        }
+
+        let (tx, rx) = mpsc::sync_channel::<Option<ParsedStatus>>(1);
+        let data = Box::into_raw(Box::new(tx));
+
+        unsafe{
+            let em = sys::libvlc_media_event_manager(self.ptr);
+            if sys::libvlc_event_attach(
+                em, EventType::MediaParsedChanged as i32, parsed_cb, data as *mut c_void) != 0 {
+                drop(Box::from_raw(data));
+                return None;
+            }
+
+            let mut status = None;
+            if self.parse_with_options(flags, timeout) {
+                // libvlc enforces the timeout of a new request. The request may also have been
+                // ignored because another one is pending, whose end may have been sent already,
+                // so the status is polled as well, until a bound derived from the timeout.
+                let deadline = parse_deadline(timeout);
+                while !cancelled.load(Ordering::SeqCst) {
+                    if let Ok(s) = rx.recv_timeout(PARSE_POLL_INTERVAL) {
+                        status = s;
+                        break;
+                    }
+                    if let Some(s) = self.parsed_status() {
+                        status = Some(s);
+                        break;
+                    }
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        status = Some(ParsedStatus::Timeout);
+                        break;
+                    }
+                }
+                if status.is_none() && cancelled.load(Ordering::SeqCst) {
+                    self.parse_stop();
+                }
+            }
+
+            sys::libvlc_event_detach(
+                em, EventType::MediaParsedChanged as i32, parsed_cb, data as *mut c_void);
+            drop(Box::from_raw(data));
+            status
+        }
+    }
+
+    /// Stop the parsing of the media.
+    /// The MediaParsedChanged event is sent with ParsedStatus::Timeout.
+    pub fn parse_stop(&self) {
+        unsafe{ sys::libvlc_media_parse_stop(self.ptr) };
+    }
+
+    /// Get the parsed status of the media, or None if it has not been parsed yet.
+    pub fn parsed_status(&self) -> Option<ParsedStatus> {
+        parsed_status_from(unsafe{ sys::libvlc_media_get_parsed_status(self.ptr) })
+    }
+
+    /// Get subitems of media descriptor object.
+    /// Subitems are filled in by parsing playlists, directories and other containers.
+    /// The returned MediaList holds a new reference.
+    pub fn subitems(&self) -> Option<MediaList> {
+        unsafe{
+            let p = sys::libvlc_media_subitems(self.ptr);
+            if p.is_null() { None }else{ Some(MediaList{ptr: p}) }
+        }
+    }
+
+    /// Recursively parse the media and its subitems into a tree, down to `max_depth` levels.
+    /// Media that are not parsed yet are parsed synchronously.
+    /// A subitem which is the same media as one of its ancestors is skipped, to break cycles.
+    pub fn expand(&self, max_depth: usize) -> MediaTree {
+        expand_media(self.clone(), max_depth, &mut Vec::new())
+    }
+
     pub fn tracks(&self) -> Option<Vec<MediaTrack>> {
         unsafe{
             let mut p_track: *mut *mut sys::libvlc_media_track_t = ::std::ptr::null_mut();
@@ -184,19 +460,395 @@ impl Media {
         }
     }
 
+    /// Get the current statistics about the media.
+    /// The statistics are only updated while the media is played.
+    pub fn stats(&self) -> Option<MediaStats> {
+        unsafe{
+            let mut stats: sys::libvlc_media_stats_t = ::std::mem::zeroed();
+            if sys::libvlc_media_get_stats(self.ptr, &mut stats) == 0 {
+                return None;
+            }
+
+            Some(MediaStats{
+                read_bytes:          stats.i_read_bytes,
+                input_bitrate:       stats.f_input_bitrate,
+                demux_read_bytes:    stats.i_demux_read_bytes,
+                demux_bitrate:       stats.f_demux_bitrate,
+                demux_corrupted:     stats.i_demux_corrupted,
+                demux_discontinuity: stats.i_demux_discontinuity,
+                decoded_video:       stats.i_decoded_video,
+                decoded_audio:       stats.i_decoded_audio,
+                displayed_pictures:  stats.i_displayed_pictures,
+                lost_pictures:       stats.i_lost_pictures,
+                played_abuffers:     stats.i_played_abuffers,
+                lost_abuffers:       stats.i_lost_abuffers,
+                sent_packets:        stats.i_sent_packets,
+                sent_bytes:          stats.i_sent_bytes,
+                send_bitrate:        stats.f_send_bitrate,
+            })
+        }
+    }
+
     /// Returns raw pointer
     pub fn raw(&self) -> *mut sys::libvlc_media_t {
         self.ptr
     }
 }
 
+impl Clone for Media {
+    /// Increments the reference count of the media.
+    fn clone(&self) -> Media {
+        unsafe{ sys::libvlc_media_retain(self.ptr) };
+        Media{ptr: self.ptr}
+    }
+}
+
 impl Drop for Media {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_release(self.ptr) };
     }
 }
 
+pub(crate) const ALL_META: &[Meta] = &[
+    Meta::Title, Meta::Artist, Meta::Genre, Meta::Copyright, Meta::Album, Meta::TrackNumber,
+    Meta::Description, Meta::Rating, Meta::Date, Meta::Setting, Meta::URL, Meta::Language,
+    Meta::NowPlaying, Meta::Publisher, Meta::EncodedBy, Meta::ArtworkURL, Meta::TrackID,
+    Meta::TrackTotal, Meta::Director, Meta::Season, Meta::Episode, Meta::ShowName, Meta::Actors,
+];
+
+/// An option added to a media.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct MediaOption {
+    pub option: String,
+    pub trusted: bool,
+    pub unique: bool,
+}
+
+/// Builder for media options, applied with Media::add_options().
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MediaOptions {
+    options: Vec<MediaOption>,
+}
+
+impl MediaOptions {
+    pub fn new() -> MediaOptions {
+        MediaOptions::default()
+    }
+
+    /// Add a free-form option, e.g. ":http-reconnect".
+    /// Only trusted options may set options which are unsafe for untrusted input, see Media::add_option_flag().
+    pub fn option(self, option: &str, trusted: bool) -> MediaOptions {
+        self.option_flag(option, trusted, false)
+    }
+
+    /// Add a free-form option with flags, see Media::add_option_flag().
+    pub fn option_flag(mut self, option: &str, trusted: bool, unique: bool) -> MediaOptions {
+        self.options.push(MediaOption{ option: option.to_owned(), trusted, unique });
+        self
+    }
+
+    /// Caching value for network resources (in ms).
+    pub fn network_caching(self, ms: u32) -> MediaOptions {
+        self.typed(&format!(":network-caching={}", ms))
+    }
+
+    /// Caching value for local files (in ms).
+    pub fn file_caching(self, ms: u32) -> MediaOptions {
+        self.typed(&format!(":file-caching={}", ms))
+    }
+
+    /// Start the media at this position (in seconds).
+    pub fn start_time(self, secs: f64) -> MediaOptions {
+        self.typed(&format!(":start-time={}", secs))
+    }
+
+    /// Stop the media at this position (in seconds).
+    pub fn stop_time(self, secs: f64) -> MediaOptions {
+        self.typed(&format!(":stop-time={}", secs))
+    }
+
+    /// Play the media for this duration (in seconds).
+    pub fn run_time(self, secs: f64) -> MediaOptions {
+        self.typed(&format!(":run-time={}", secs))
+    }
+
+    /// Repeat the media this number of times.
+    pub fn input_repeat(self, count: u32) -> MediaOptions {
+        self.typed(&format!(":input-repeat={}", count))
+    }
+
+    /// Disable video.
+    pub fn no_video(self) -> MediaOptions {
+        self.typed(":no-video")
+    }
+
+    /// Disable audio.
+    pub fn no_audio(self) -> MediaOptions {
+        self.typed(":no-audio")
+    }
+
+    /// Load this subtitle file.
+    /// The option is untrusted, see trusted().
+    pub fn sub_file<T: AsRef<Path>>(self, path: T) -> MediaOptions {
+        self.option(&format!(":sub-file={}", path.as_ref().to_string_lossy()), false)
+    }
+
+    /// Select the audio track by id.
+    pub fn audio_track(self, id: i32) -> MediaOptions {
+        self.typed(&format!(":audio-track={}", id))
+    }
+
+    /// Select the video track by id.
+    pub fn video_track(self, id: i32) -> MediaOptions {
+        self.typed(&format!(":video-track={}", id))
+    }
+
+    /// Stream output chain, e.g. "#transcode{vcodec=h264}:std{access=file,dst=out.mp4}".
+    /// The option is untrusted, so libvlc ignores it unless it is marked with trusted().
+    pub fn sout(self, chain: &str) -> MediaOptions {
+        self.option(&format!(":sout={}", chain), false)
+    }
+
+    /// Mark the last added option as trusted.
+    /// Only do so if its value does not come from untrusted input, since a trusted
+    /// stream output chain may write anywhere.
+    pub fn trusted(mut self) -> MediaOptions {
+        if let Some(option) = self.options.last_mut() {
+            option.trusted = true;
+        }
+        self
+    }
+
+    /// Returns the options in the order they will be applied.
+    pub fn options(&self) -> &[MediaOption] {
+        &self.options
+    }
+
+    // The typed options only take numbers or fixed names, so they are trusted.
+    // Options taking a path or a chain are untrusted, see sout().
+    fn typed(self, option: &str) -> MediaOptions {
+        self.option_flag(option, true, false)
+    }
+}
+
+/// A media and its expanded subitems, see Media::expand().
+#[derive(Clone)]
+pub struct MediaTree {
+    pub media: Media,
+    pub children: Vec<MediaTree>,
+}
+
+impl MediaTree {
+    /// Returns the media without children, in depth-first order.
+    /// Media at the depth limit are not expanded, so they are returned as well.
+    pub fn flatten(&self) -> Vec<Media> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<Media>) {
+        if self.children.is_empty() {
+            leaves.push(self.media.clone());
+        }
+        for child in &self.children {
+            child.collect_leaves(leaves);
+        }
+    }
+}
+
+// MRL of the media created by Media::new_node(), which does not identify a node.
+const NODE_MRL: &str = "vlc://nop";
+
+// Identity of a media for cycle detection: its pointer, and its MRL unless it is a node.
+type MediaKey = (*mut sys::libvlc_media_t, Option<String>);
+
+fn media_key(md: &Media) -> MediaKey {
+    (md.ptr, md.mrl().filter(|mrl| mrl != NODE_MRL))
+}
+
+fn expand_media(md: Media, depth: usize, ancestors: &mut Vec<MediaKey>) -> MediaTree {
+    let mut children = Vec::new();
+
+    if depth > 0 {
+        if !md.is_parsed() {
+            md.parse();
+        }
+
+        if let Some(list) = md.subitems() {
+            list.lock();
+            let items: Vec<Media> = (0..list.count()).filter_map(|i| list.item_at_index(i)).collect();
+            list.unlock();
+
+            ancestors.push(media_key(&md));
+            for item in items {
+                let (ptr, mrl) = media_key(&item);
+                let is_cycle = ancestors.iter().any(|&(a_ptr, ref a_mrl)| {
+                    a_ptr == ptr || (mrl.is_some() && *a_mrl == mrl)
+                });
+                if !is_cycle {
+                    children.push(expand_media(item, depth - 1, ancestors));
+                }
+            }
+            ancestors.pop();
+        }
+    }
+
+    MediaTree{media: md, children}
+}
+
+// Converts a libvlc_media_parsed_status_t value, which may be unknown or 0 (not parsed).
+pub(crate) fn parsed_status_from(status: c_int) -> Option<ParsedStatus> {
+    match status {
+        1 => Some(ParsedStatus::Skipped),
+        2 => Some(ParsedStatus::Failed),
+        3 => Some(ParsedStatus::Timeout),
+        4 => Some(ParsedStatus::Done),
+        _ => None,
+    }
+}
+
+// Interval at which Media::parse_until checks for cancellation.
+const PARSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Default of the "preparse-timeout" option, used by libvlc for a timeout of -1.
+const DEFAULT_PARSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Time left to libvlc after the timeout to send the end of parsing.
+const PARSE_TIMEOUT_MARGIN: Duration = Duration::from_secs(1);
+
+// When Media::parse_until stops waiting, None for a timeout of 0 which waits forever.
+fn parse_deadline(timeout: i32) -> Option<Instant> {
+    let timeout = match timeout {
+        0 => return None,
+        t if t < 0 => DEFAULT_PARSE_TIMEOUT,
+        t => Duration::from_millis(t as u64),
+    };
+    Some(Instant::now() + timeout + PARSE_TIMEOUT_MARGIN)
+}
+
+unsafe extern "C" fn parsed_cb(pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    let tx = &*(data as *const SyncSender<Option<ParsedStatus>>);
+    let _ = tx.try_send(parsed_status_from((*pe).u.media_parsed_changed.new_status));
+}
+
+// Stored as the libvlc user data of a media, for Media::set_user_data and Media::applied_options
+#[derive(Default)]
+struct MediaData {
+    user_data: Mutex<Option<Arc<dyn Any + Send + Sync>>>,
+    options: Mutex<Vec<MediaOption>>,
+    // Created by Media::new_callbacks
+    callbacks: AtomicBool,
+}
+
+// Serializes the creation of media data.
+static MEDIA_DATA_LOCK: Mutex<()> = Mutex::new(());
+
+unsafe extern "C" fn media_data_free(_pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    drop(Box::from_raw(data as *mut MediaData));
+}
+
+// For Media::from_reader and Media::from_read
+trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+enum MediaInput {
+    Seekable(Box<dyn ReadSeek + Send>),
+    Stream(Box<dyn Read + Send>),
+}
+
+type MediaInputData = Mutex<MediaInput>;
+
+impl MediaInput {
+    fn is_seekable(&self) -> bool {
+        match *self {
+            MediaInput::Seekable(_) => true,
+            MediaInput::Stream(_) => false,
+        }
+    }
+
+    // Returns the size of the data, or u64::MAX if unknown.
+    fn open(&mut self) -> io::Result<u64> {
+        match *self {
+            MediaInput::Seekable(ref mut r) => {
+                let size = r.seek(SeekFrom::End(0))?;
+                r.seek(SeekFrom::Start(0))?;
+                Ok(size)
+            },
+            MediaInput::Stream(_) => Ok(u64::MAX),
+        }
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let result = match *self {
+                MediaInput::Seekable(ref mut r) => r.read(buf),
+                MediaInput::Stream(ref mut r) => r.read(buf),
+            };
+            match result {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+
+    fn seek(&mut self, offset: u64) -> io::Result<()> {
+        match *self {
+            MediaInput::Seekable(ref mut r) => r.seek(SeekFrom::Start(offset)).map(|_| ()),
+            MediaInput::Stream(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "not seekable")),
+        }
+    }
+}
+
+// Runs f with the locked input, catching panics so that they never unwind into libvlc.
+unsafe fn with_media_input<T, F>(opaque: *mut c_void, f: F) -> Option<T>
+    where F: FnOnce(&mut MediaInput) -> io::Result<T>
+{
+    let data = &*(opaque as *const MediaInputData);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        match data.lock() {
+            Ok(mut input) => f(&mut input).ok(),
+            Err(_) => None,
+        }
+    }));
+    result.unwrap_or(None)
+}
+
+unsafe extern "C" fn media_input_open(
+    opaque: *mut c_void, datap: *mut *mut c_void, sizep: *mut u64) -> c_int {
+    *datap = opaque;
+    match with_media_input(opaque, |input| input.open()) {
+        Some(size) => { *sizep = size; 0 },
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn media_input_read(opaque: *mut c_void, buf: *mut c_uchar, len: usize) -> ssize_t {
+    let buf = ::std::slice::from_raw_parts_mut(buf, len);
+    match with_media_input(opaque, |input| input.read(buf)) {
+        Some(n) => n as ssize_t,
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn media_input_seek(opaque: *mut c_void, offset: u64) -> c_int {
+    match with_media_input(opaque, |input| input.seek(offset)) {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn media_input_close(_opaque: *mut c_void) {
+    // The input is kept until the media is freed, since the media may be opened again.
+}
+
+unsafe extern "C" fn media_input_free(_pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    drop(Box::from_raw(data as *mut MediaInputData));
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MediaTrack {
     pub codec: u32,
     pub original_fourcc: u32,
@@ -211,17 +863,20 @@ pub struct MediaTrack {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MediaTrackUnion {
     Audio(AudioTrack), Video(VideoTrack), Subtitle(SubtitleTrack), None,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AudioTrack {
     pub channels: u32,
     pub rate: u32,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VideoTrack {
     pub height: u32,
     pub width: u32,
@@ -232,7 +887,29 @@ pub struct VideoTrack {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubtitleTrack {
     pub encoding: Option<String>,
 }
 
+/// Statistics of a media, see Media::stats().
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaStats {
+    pub read_bytes: i32,
+    pub input_bitrate: f32,
+    pub demux_read_bytes: i32,
+    pub demux_bitrate: f32,
+    pub demux_corrupted: i32,
+    pub demux_discontinuity: i32,
+    pub decoded_video: i32,
+    pub decoded_audio: i32,
+    pub displayed_pictures: i32,
+    pub lost_pictures: i32,
+    pub played_abuffers: i32,
+    pub lost_abuffers: i32,
+    pub sent_packets: i32,
+    pub sent_bytes: i32,
+    pub send_bitrate: f32,
+}
+
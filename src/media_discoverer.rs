@@ -0,0 +1,151 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::error;
+use std::fmt;
+use crate::sys;
+use crate::{Instance, MediaList, EventManager};
+use crate::enums::MediaDiscovererCategory;
+use crate::tools::{to_cstr, from_cstr};
+
+/// Media discoverer, used to browse network shares, devices, podcasts and local directories.
+///
+/// It holds a reference to the libvlc instance it was created from, and is Send.
+pub struct MediaDiscoverer {
+    pub(crate) ptr: *mut sys::libvlc_media_discoverer_t,
+}
+
+unsafe impl Send for MediaDiscoverer {}
+
+impl MediaDiscoverer {
+    /// Create a media discoverer object by name.
+    /// The name is one of the services returned by MediaDiscoverer::list_get().
+    /// After this object is created, you should call start() in order to start the discovery.
+    pub fn new(instance: &Instance, name: &str) -> Option<MediaDiscoverer> {
+        let cstr = to_cstr(name);
+
+        unsafe{
+            let p = sys::libvlc_media_discoverer_new(instance.ptr, cstr.as_ptr());
+            if p.is_null() { None }else{ Some(MediaDiscoverer{ptr: p}) }
+        }
+    }
+
+    /// Get media discoverer services by category.
+    pub fn list_get(instance: &Instance, category: MediaDiscovererCategory) -> Vec<MediaDiscovererDescription> {
+        unsafe{
+            let mut p_services: *mut *mut sys::libvlc_media_discoverer_description_t = ::std::ptr::null_mut();
+            let n = sys::libvlc_media_discoverer_list_get(instance.ptr, category, &mut p_services);
+
+            let mut services = Vec::with_capacity(n);
+
+            for i in 0..n {
+                let p = *p_services.add(i);
+                services.push(MediaDiscovererDescription{
+                    name:     from_cstr((*p).psz_name),
+                    longname: from_cstr((*p).psz_longname),
+                    category: match (*p).i_cat {
+                        0 => MediaDiscovererCategory::Devices,
+                        1 => MediaDiscovererCategory::Lan,
+                        2 => MediaDiscovererCategory::Podcasts,
+                        3 => MediaDiscovererCategory::LocalDirs,
+                        // The services are listed by category.
+                        _ => category,
+                    },
+                });
+            }
+
+            if n > 0 {
+                sys::libvlc_media_discoverer_list_release(p_services, n);
+            }
+            services
+        }
+    }
+
+    /// Start media discovery.
+    /// To stop it, call stop() or drop the media discoverer.
+    pub fn start(&self) -> Result<(), DiscovererError> {
+        unsafe{
+            if sys::libvlc_media_discoverer_start(self.ptr) == 0 {
+                Ok(())
+            } else {
+                let msg = crate::errmsg();
+                crate::clearerr();
+                Err(DiscovererError(msg))
+            }
+        }
+    }
+
+    /// Stop media discovery.
+    pub fn stop(&self) {
+        unsafe{ sys::libvlc_media_discoverer_stop(self.ptr) };
+    }
+
+    /// Get media service discover object its localized name.
+    pub fn localized_name(&self) -> Option<String> {
+        unsafe{
+            let p_str = sys::libvlc_media_discoverer_localized_name(self.ptr);
+            let s = from_cstr(p_str);
+            sys::libvlc_free(p_str as *mut ::libc::c_void);
+            s
+        }
+    }
+
+    /// Get media service discover media list.
+    /// The returned MediaList holds a new reference.
+    pub fn media_list(&self) -> Option<MediaList> {
+        unsafe{
+            let p = sys::libvlc_media_discoverer_media_list(self.ptr);
+            if p.is_null() { None }else{ Some(MediaList{ptr: p}) }
+        }
+    }
+
+    /// Get event manager from media service discover object.
+    pub fn event_manager<'a>(&'a self) -> EventManager<'a> {
+        unsafe{
+            let p = sys::libvlc_media_discoverer_event_manager(self.ptr);
+            assert!(!p.is_null());
+            EventManager{ptr: p, _phantomdata: ::std::marker::PhantomData}
+        }
+    }
+
+    /// Query if media service discover object is running.
+    pub fn is_running(&self) -> bool {
+        unsafe{ sys::libvlc_media_discoverer_is_running(self.ptr) != 0 }
+    }
+
+    /// Returns raw pointer
+    pub fn raw(&self) -> *mut sys::libvlc_media_discoverer_t {
+        self.ptr
+    }
+}
+
+impl Drop for MediaDiscoverer {
+    fn drop(&mut self) {
+        unsafe{ sys::libvlc_media_discoverer_release(self.ptr) };
+    }
+}
+
+/// Error returned when a media discoverer cannot be started, with the libvlc message if any.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DiscovererError(pub Option<String>);
+
+impl fmt::Display for DiscovererError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(ref msg) => write!(f, "cannot start the media discoverer: {}", msg),
+            None => write!(f, "cannot start the media discoverer"),
+        }
+    }
+}
+
+impl error::Error for DiscovererError {}
+
+/// Description of a media discoverer service.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaDiscovererDescription {
+    pub name: Option<String>,
+    pub longname: Option<String>,
+    pub category: MediaDiscovererCategory,
+}
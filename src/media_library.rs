@@ -5,10 +5,15 @@
 use crate::sys;
 use crate::{Instance, MediaList};
 
+/// A media library.
+///
+/// It holds a reference to the libvlc instance it was created from, and is Send.
 pub struct MediaLibrary {
     pub(crate) ptr: *mut sys::libvlc_media_library_t,
 }
 
+unsafe impl Send for MediaLibrary {}
+
 impl MediaLibrary {
     /// Create an new Media Library object.
     pub fn new(instance: &Instance) -> Option<MediaLibrary> {
@@ -26,6 +31,7 @@ impl MediaLibrary {
     }
 
     /// Get media library subitems.
+    /// The returned MediaList holds a new reference.
     pub fn media_list(&self) -> Option<MediaList> {
         unsafe{
             let p = sys::libvlc_media_library_media_list(self.ptr);
@@ -5,10 +5,16 @@
 use crate::sys;
 use crate::{Instance, Media, EventManager};
 
+/// A list of media.
+///
+/// It holds a reference to the libvlc instance it was created from.
+/// It is Send, but not Sync since most functions require the caller to hold MediaList::lock.
 pub struct MediaList {
     pub(crate) ptr: *mut sys::libvlc_media_list_t,
 }
 
+unsafe impl Send for MediaList {}
+
 impl MediaList {
     /// Create an empty media list.
     pub fn new(instance: &Instance) -> Option<MediaList> {
@@ -26,6 +32,7 @@ impl MediaList {
 
     /// Get media instance from this media list instance.
     /// The MediaList::lock should NOT be held upon entering this function.
+    /// The returned Media holds a new reference.
     pub fn media(&self) -> Option<Media> {
         unsafe{
             let p = sys::libvlc_media_list_media(self.ptr);
@@ -35,6 +42,7 @@ impl MediaList {
 
     /// Add media instance to media list.
     /// The MediaList::lock should be held upon entering this function.
+    /// The media list holds its own reference to md.
     pub fn add_media(&self, md: &Media) -> Result<(), ()> {
         unsafe{
             if sys::libvlc_media_list_add_media(self.ptr, md.ptr) == 0 { Ok(()) }else{ Err(()) }
@@ -65,6 +73,7 @@ impl MediaList {
 
     /// List media instance in media list at a position.
     /// The MediaList::lock should be held upon entering this function.
+    /// The returned Media holds a new reference, so it stays valid after it is removed from the list.
     pub fn item_at_index(&self, pos: i32) -> Option<Media> {
         unsafe{
             let p = sys::libvlc_media_list_item_at_index(self.ptr, pos);
@@ -111,6 +120,14 @@ impl MediaList {
     }
 }
 
+impl Clone for MediaList {
+    /// Increments the reference count of the media list.
+    fn clone(&self) -> MediaList {
+        unsafe{ sys::libvlc_media_list_retain(self.ptr) };
+        MediaList{ptr: self.ptr}
+    }
+}
+
 impl Drop for MediaList {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_list_release(self.ptr) };
@@ -0,0 +1,124 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use crate::sys;
+use crate::{Instance, Media, MediaList, MediaPlayer, EventManager};
+use crate::enums::{State, PlaybackMode};
+
+/// The LibVLC media list player plays a MediaList using a MediaPlayer.
+///
+/// It holds a reference to the libvlc instance it was created from, and is Send.
+pub struct MediaListPlayer {
+    pub(crate) ptr: *mut sys::libvlc_media_list_player_t,
+}
+
+unsafe impl Send for MediaListPlayer {}
+
+impl MediaListPlayer {
+    /// Create new media_list_player.
+    pub fn new(instance: &Instance) -> Option<MediaListPlayer> {
+        unsafe{
+            let p = sys::libvlc_media_list_player_new(instance.ptr);
+            if p.is_null() { None }else{ Some(MediaListPlayer{ptr: p}) }
+        }
+    }
+
+    /// Return the event manager of this media_list_player.
+    pub fn event_manager<'a>(&'a self) -> EventManager<'a> {
+        unsafe{
+            let p = sys::libvlc_media_list_player_event_manager(self.ptr);
+            assert!(!p.is_null());
+            EventManager{ptr: p, _phantomdata: ::std::marker::PhantomData}
+        }
+    }
+
+    /// Replace media player in media_list_player with this instance.
+    pub fn set_media_player(&self, mp: &MediaPlayer) {
+        unsafe{ sys::libvlc_media_list_player_set_media_player(self.ptr, mp.ptr) };
+    }
+
+    /// Get media player of the media_list_player instance.
+    /// The returned MediaPlayer holds a new reference.
+    pub fn get_media_player(&self) -> Option<MediaPlayer> {
+        unsafe{
+            let p = sys::libvlc_media_list_player_get_media_player(self.ptr);
+            if p.is_null() { None }else{ Some(MediaPlayer{ptr: p}) }
+        }
+    }
+
+    /// Set the media list associated with the player.
+    pub fn set_media_list(&self, mlist: &MediaList) {
+        unsafe{ sys::libvlc_media_list_player_set_media_list(self.ptr, mlist.ptr) };
+    }
+
+    /// Play media list.
+    pub fn play(&self) {
+        unsafe{ sys::libvlc_media_list_player_play(self.ptr) };
+    }
+
+    /// Toggle pause (or resume) media list.
+    pub fn pause(&self) {
+        unsafe{ sys::libvlc_media_list_player_pause(self.ptr) };
+    }
+
+    /// Pause or resume media list.
+    pub fn set_pause(&self, do_pause: bool) {
+        unsafe{ sys::libvlc_media_list_player_set_pause(self.ptr, if do_pause {1} else {0}) };
+    }
+
+    /// Is media list playing?
+    pub fn is_playing(&self) -> bool {
+        unsafe{ sys::libvlc_media_list_player_is_playing(self.ptr) != 0 }
+    }
+
+    /// Get current libvlc_state of media list player.
+    pub fn state(&self) -> State {
+        unsafe{ sys::libvlc_media_list_player_get_state(self.ptr) }
+    }
+
+    /// Play media list item at position index.
+    /// Returns false if there is no item at this index.
+    pub fn play_item_at_index(&self, index: i32) -> bool {
+        unsafe{ sys::libvlc_media_list_player_play_item_at_index(self.ptr, index) == 0 }
+    }
+
+    /// Play the given media item.
+    /// Returns false if the media is not in the list.
+    pub fn play_item(&self, md: &Media) -> bool {
+        unsafe{ sys::libvlc_media_list_player_play_item(self.ptr, md.ptr) == 0 }
+    }
+
+    /// Stop playing media list.
+    pub fn stop(&self) {
+        unsafe{ sys::libvlc_media_list_player_stop(self.ptr) };
+    }
+
+    /// Play next item from media list.
+    /// Returns false if there is no next item.
+    pub fn next(&self) -> bool {
+        unsafe{ sys::libvlc_media_list_player_next(self.ptr) == 0 }
+    }
+
+    /// Play previous item from media list.
+    /// Returns false if there is no previous item.
+    pub fn previous(&self) -> bool {
+        unsafe{ sys::libvlc_media_list_player_previous(self.ptr) == 0 }
+    }
+
+    /// Sets the playback mode for the playlist.
+    pub fn set_playback_mode(&self, mode: PlaybackMode) {
+        unsafe{ sys::libvlc_media_list_player_set_playback_mode(self.ptr, mode) };
+    }
+
+    /// Returns raw pointer
+    pub fn raw(&self) -> *mut sys::libvlc_media_list_player_t {
+        self.ptr
+    }
+}
+
+impl Drop for MediaListPlayer {
+    fn drop(&mut self) {
+        unsafe{ sys::libvlc_media_list_player_release(self.ptr) };
+    }
+}
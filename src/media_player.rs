@@ -11,12 +11,17 @@ use crate::enums::{State, Position};
 use std::mem::transmute;
 
 /// A LibVLC media player plays one media (usually in a custom drawable).
+///
+/// It holds a reference to the libvlc instance it was created from.
+/// libvlc locks the media player internally, so it is Send and Sync.
 pub struct MediaPlayer {
     pub(crate) ptr: *mut sys::libvlc_media_player_t,
 }
 
 unsafe impl Send for MediaPlayer {}
 
+unsafe impl Sync for MediaPlayer {}
+
 impl MediaPlayer {
     /// Create an empty Media Player object
     pub fn new(instance: &Instance) -> Option<MediaPlayer> {
@@ -31,11 +36,13 @@ impl MediaPlayer {
     }
 
     /// Set the media that will be used by the media_player. If any, previous md will be released.
+    /// The media player holds its own reference to md.
     pub fn set_media(&self, md: &Media) {
         unsafe{ sys::libvlc_media_player_set_media(self.ptr, md.ptr) };
     }
 
     /// Get the media used by the media_player.
+    /// The returned Media holds a new reference, so it stays valid after the media is changed.
     pub fn get_media(&self) -> Option<Media> {
         let p = unsafe{ sys::libvlc_media_player_get_media(self.ptr) };
         if p.is_null() {
@@ -320,6 +327,14 @@ impl MediaPlayer {
     }
 }
 
+impl Clone for MediaPlayer {
+    /// Increments the reference count of the media player.
+    fn clone(&self) -> MediaPlayer {
+        unsafe{ sys::libvlc_media_player_retain(self.ptr) };
+        MediaPlayer{ptr: self.ptr}
+    }
+}
+
 impl Drop for MediaPlayer {
     fn drop(&mut self) {
         unsafe{ sys::libvlc_media_player_release(self.ptr) };
@@ -363,6 +378,7 @@ unsafe extern "C" fn audio_cb_drain(data: *mut c_void) {
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackDescription {
     pub id: i32,
     pub name: Option<String>,
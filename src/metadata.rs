@@ -0,0 +1,185 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::Media;
+use crate::enums::Meta;
+use crate::media::ALL_META;
+
+/// All the meta of a media, see Media::metadata().
+/// Numeric meta are None if they are not set or cannot be parsed.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub genre: Option<String>,
+    pub copyright: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub description: Option<String>,
+    pub rating: Option<u32>,
+    pub date: Option<MetaDate>,
+    pub setting: Option<String>,
+    pub url: Option<String>,
+    pub language: Option<String>,
+    pub now_playing: Option<String>,
+    pub publisher: Option<String>,
+    pub encoded_by: Option<String>,
+    pub artwork_url: Option<String>,
+    pub track_id: Option<String>,
+    pub track_total: Option<u32>,
+    pub director: Option<String>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub show_name: Option<String>,
+    pub actors: Option<String>,
+}
+
+impl MediaMetadata {
+    /// Returns a meta as the string stored by libvlc.
+    pub fn get(&self, meta: Meta) -> Option<String> {
+        match meta {
+            Meta::Title       => self.title.clone(),
+            Meta::Artist      => self.artist.clone(),
+            Meta::Genre       => self.genre.clone(),
+            Meta::Copyright   => self.copyright.clone(),
+            Meta::Album       => self.album.clone(),
+            Meta::TrackNumber => self.track_number.map(|n| n.to_string()),
+            Meta::Description => self.description.clone(),
+            Meta::Rating      => self.rating.map(|n| n.to_string()),
+            Meta::Date        => self.date.map(|d| d.to_string()),
+            Meta::Setting     => self.setting.clone(),
+            Meta::URL         => self.url.clone(),
+            Meta::Language    => self.language.clone(),
+            Meta::NowPlaying  => self.now_playing.clone(),
+            Meta::Publisher   => self.publisher.clone(),
+            Meta::EncodedBy   => self.encoded_by.clone(),
+            Meta::ArtworkURL  => self.artwork_url.clone(),
+            Meta::TrackID     => self.track_id.clone(),
+            Meta::TrackTotal  => self.track_total.map(|n| n.to_string()),
+            Meta::Director    => self.director.clone(),
+            Meta::Season      => self.season.map(|n| n.to_string()),
+            Meta::Episode     => self.episode.map(|n| n.to_string()),
+            Meta::ShowName    => self.show_name.clone(),
+            Meta::Actors      => self.actors.clone(),
+        }
+    }
+
+    /// Set a meta from the string stored by libvlc.
+    pub fn set(&mut self, meta: Meta, value: Option<&str>) {
+        let string = value.map(|s| s.to_owned());
+        let number = value.and_then(|s| s.trim().parse().ok());
+        match meta {
+            Meta::Title       => self.title = string,
+            Meta::Artist      => self.artist = string,
+            Meta::Genre       => self.genre = string,
+            Meta::Copyright   => self.copyright = string,
+            Meta::Album       => self.album = string,
+            Meta::TrackNumber => self.track_number = number,
+            Meta::Description => self.description = string,
+            Meta::Rating      => self.rating = number,
+            Meta::Date        => self.date = value.and_then(|s| s.parse().ok()),
+            Meta::Setting     => self.setting = string,
+            Meta::URL         => self.url = string,
+            Meta::Language    => self.language = string,
+            Meta::NowPlaying  => self.now_playing = string,
+            Meta::Publisher   => self.publisher = string,
+            Meta::EncodedBy   => self.encoded_by = string,
+            Meta::ArtworkURL  => self.artwork_url = string,
+            Meta::TrackID     => self.track_id = string,
+            Meta::TrackTotal  => self.track_total = number,
+            Meta::Director    => self.director = string,
+            Meta::Season      => self.season = number,
+            Meta::Episode     => self.episode = number,
+            Meta::ShowName    => self.show_name = string,
+            Meta::Actors      => self.actors = string,
+        }
+    }
+}
+
+/// Date of a media. The month and day are optional, as in "2004" or "2004-05".
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetaDate {
+    pub year: i32,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl FromStr for MetaDate {
+    type Err = ();
+
+    /// Parse "YYYY", "YYYY-MM" or "YYYY-MM-DD", ignoring a trailing time as in "2004-05-12T10:00:00".
+    fn from_str(s: &str) -> Result<MetaDate, ()> {
+        let s = s.trim();
+        let s = s.split(['T', ' ']).next().unwrap_or(s);
+        let mut parts = s.split('-');
+
+        let year = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let month = match parts.next() {
+            Some(m) => Some(m.parse().ok().filter(|m| (1..=12).contains(m)).ok_or(())?),
+            None => None,
+        };
+        let day = match parts.next() {
+            Some(d) => Some(d.parse().ok().filter(|d| (1..=31).contains(d)).ok_or(())?),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return Err(());
+        }
+
+        Ok(MetaDate{ year, month, day })
+    }
+}
+
+impl fmt::Display for MetaDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{:02}", month)?;
+            if let Some(day) = self.day {
+                write!(f, "-{:02}", day)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Media {
+    /// Read all the meta of the media.
+    /// Before the media is parsed, the fields are None unless they were set with set_meta(),
+    /// except the title which falls back to the name of the media (e.g. the file name).
+    pub fn metadata(&self) -> MediaMetadata {
+        let mut metadata = MediaMetadata::default();
+        for &meta in ALL_META {
+            metadata.set(meta, self.get_meta(meta).as_deref());
+        }
+        metadata
+    }
+
+    /// Set the meta which are not None in `metadata`, then save them.
+    /// On failure, returns the meta which were not saved.
+    pub fn set_metadata(&self, metadata: &MediaMetadata) -> Result<(), Vec<Meta>> {
+        let mut changed = Vec::new();
+        let mut failed = Vec::new();
+
+        for &meta in ALL_META {
+            if let Some(value) = metadata.get(meta) {
+                self.set_meta(meta, &value);
+                if self.get_meta(meta).as_deref() == Some(value.as_str()) {
+                    changed.push(meta);
+                } else {
+                    failed.push(meta);
+                }
+            }
+        }
+
+        if !changed.is_empty() && !self.save_meta() {
+            failed.extend(changed);
+        }
+        if failed.is_empty() { Ok(()) }else{ Err(failed) }
+    }
+}
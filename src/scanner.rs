@@ -0,0 +1,270 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use crate::{Instance, Media, MediaTrack};
+use crate::enums::{Meta, ParseFlag, ParsedStatus};
+
+/// File extensions of the media played by VLC, used by default by the Scanner.
+pub const MEDIA_EXTENSIONS: &[&str] = &[
+    // Audio
+    "3ga", "669", "a52", "aac", "ac3", "adt", "adts", "aif", "aifc", "aiff", "amb", "amr",
+    "aob", "ape", "au", "awb", "caf", "dts", "dsf", "dff", "flac", "it", "kar", "m4a", "m4b",
+    "m4p", "m5p", "mid", "mka", "mlp", "mod", "mpa", "mp1", "mp2", "mp3", "mpc", "mpga", "mus",
+    "oga", "ogg", "oma", "opus", "qcp", "ra", "rmi", "s3m", "sid", "spx", "tak", "thd", "tta",
+    "voc", "vqf", "w64", "wav", "wma", "wv", "xa", "xm",
+    // Video
+    "3g2", "3gp", "3gp2", "3gpp", "amv", "asf", "avi", "bik", "divx", "drc", "dv", "dvr-ms",
+    "evo", "f4v", "flv", "gvi", "gxf", "m1v", "m2t", "m2ts", "m2v", "m4v", "mkv", "mov", "mp2v",
+    "mp4", "mp4v", "mpe", "mpeg", "mpeg1", "mpeg2", "mpeg4", "mpg", "mpv2", "mts", "mtv", "mxf",
+    "nsv", "nuv", "ogm", "ogv", "ogx", "ps", "rec", "rm", "rmvb", "rpl", "thp", "tod", "ts",
+    "tts", "vob", "vro", "webm", "wm", "wmv", "wtv", "xesc",
+];
+
+/// Walks a directory tree and parses the media files it contains.
+#[derive(Clone)]
+pub struct Scanner {
+    instance: Instance,
+    extensions: Vec<String>,
+    concurrency: usize,
+    timeout: i32,
+    flags: Vec<ParseFlag>,
+}
+
+impl Scanner {
+    /// Create a scanner using MEDIA_EXTENSIONS, 4 concurrent parsings and a 10 seconds timeout.
+    pub fn new(instance: &Instance) -> Scanner {
+        Scanner {
+            instance: instance.clone(),
+            extensions: MEDIA_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+            concurrency: 4,
+            timeout: 10_000,
+            flags: vec![ParseFlag::ParseLocal],
+        }
+    }
+
+    /// Set the file extensions to scan, compared case-insensitively.
+    pub fn extensions(mut self, extensions: &[&str]) -> Scanner {
+        self.extensions = extensions.iter().map(|ext| ext.to_lowercase()).collect();
+        self
+    }
+
+    /// Set the maximum number of media parsed at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Scanner {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the parsing timeout of each media (in ms).
+    pub fn timeout(mut self, timeout: i32) -> Scanner {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the parse flags, see Media::parse_with_options().
+    pub fn flags(mut self, flags: &[ParseFlag]) -> Scanner {
+        self.flags = flags.to_vec();
+        self
+    }
+
+    /// Returns true if the file has one of the scanned extensions.
+    pub fn is_media_file(&self, path: &Path) -> bool {
+        match path.extension() {
+            Some(ext) => {
+                let ext = ext.to_string_lossy().to_lowercase();
+                self.extensions.contains(&ext)
+            },
+            None => false,
+        }
+    }
+
+    /// Start scanning `dir` in background threads.
+    /// The returned handle yields the scan events, and cancels the scan when dropped.
+    pub fn scan<P: AsRef<Path>>(&self, dir: P) -> ScanHandle {
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let scanner = self.clone();
+        let dir = dir.as_ref().to_owned();
+        let cancel = cancelled.clone();
+        thread::spawn(move || scanner.run(&dir, &tx, &cancel));
+
+        ScanHandle{ rx, canceller: ScanCanceller{ cancelled } }
+    }
+
+    fn run(&self, dir: &Path, tx: &Sender<ScanEvent>, cancelled: &Arc<AtomicBool>) {
+        let mut files = Vec::new();
+        self.walk(dir, &mut files, tx);
+        files.sort();
+
+        let total = files.len();
+        let _ = tx.send(ScanEvent::Started{ total });
+
+        let queue = Arc::new(Mutex::new(files.into_iter()));
+        let done = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..self.concurrency.min(total)).map(|_| {
+            let scanner = self.clone();
+            let queue = queue.clone();
+            let done = done.clone();
+            let tx = tx.clone();
+            let cancelled = cancelled.clone();
+            thread::spawn(move || {
+                while !cancelled.load(Ordering::SeqCst) {
+                    let path = match queue.lock().unwrap().next() {
+                        Some(path) => path,
+                        None => break,
+                    };
+                    let event = match scanner.parse_file(&path, &cancelled) {
+                        Some(Ok(record)) => ScanEvent::Parsed(record),
+                        Some(Err(error)) => ScanEvent::Error{ path, error },
+                        None => break,
+                    };
+                    let _ = tx.send(event);
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    let _ = tx.send(ScanEvent::Progress{ done, total });
+                }
+            })
+        }).collect();
+
+        for worker in workers {
+            let _ = worker.join();
+        }
+
+        if cancelled.load(Ordering::SeqCst) {
+            let _ = tx.send(ScanEvent::Cancelled);
+        }
+    }
+
+    // Collects the media files under dir. Symbolic links to directories are not followed.
+    fn walk(&self, dir: &Path, files: &mut Vec<PathBuf>, tx: &Sender<ScanEvent>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                let _ = tx.send(ScanEvent::Error{ path: dir.to_owned(), error });
+                return;
+            },
+        };
+
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    let _ = tx.send(ScanEvent::Error{ path: dir.to_owned(), error });
+                    continue;
+                },
+            };
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => self.walk(&path, files, tx),
+                Ok(_) if path.is_file() && self.is_media_file(&path) => files.push(path),
+                Ok(_) => (),
+                Err(error) => { let _ = tx.send(ScanEvent::Error{ path, error }); },
+            }
+        }
+    }
+
+    // Returns None if the scan was cancelled.
+    fn parse_file(&self, path: &Path, cancelled: &AtomicBool) -> Option<io::Result<MediaRecord>> {
+        let md = match Media::new_path(&self.instance, path) {
+            Some(md) => md,
+            None => return Some(Err(io::Error::other("failed to create media"))),
+        };
+
+        let status = md.parse_until(&self.flags, self.timeout, cancelled);
+        if cancelled.load(Ordering::SeqCst) {
+            return None;
+        }
+        let status = match status {
+            Some(status) => status,
+            None => return Some(Err(io::Error::other("failed to parse"))),
+        };
+
+        Some(Ok(MediaRecord {
+            path: path.to_owned(),
+            mrl: md.mrl(),
+            status,
+            meta: md.meta_map(),
+            duration: md.duration(),
+            tracks: md.tracks().unwrap_or_default(),
+        }))
+    }
+}
+
+/// Result of parsing a file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaRecord {
+    pub path: PathBuf,
+    pub mrl: Option<String>,
+    pub status: ParsedStatus,
+    pub meta: HashMap<Meta, String>,
+    /// Duration (in ms).
+    pub duration: Option<i64>,
+    pub tracks: Vec<MediaTrack>,
+}
+
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// The directory tree was walked, and `total` files will be parsed.
+    Started { total: usize },
+    /// A file was parsed. The record holds the parsed status, which may be a failure.
+    Parsed(MediaRecord),
+    /// A file or directory could not be read.
+    Error { path: PathBuf, error: io::Error },
+    /// `done` files out of `total` were processed.
+    Progress { done: usize, total: usize },
+    /// The scan was cancelled. This is the last event.
+    Cancelled,
+}
+
+/// Handle to a running scan. Iterating over it yields the scan events until the scan ends.
+pub struct ScanHandle {
+    rx: Receiver<ScanEvent>,
+    canceller: ScanCanceller,
+}
+
+impl ScanHandle {
+    /// Returns a canceller which can be used from another thread.
+    pub fn canceller(&self) -> ScanCanceller {
+        self.canceller.clone()
+    }
+
+    /// Cancel the scan. The media being parsed are stopped.
+    pub fn cancel(&self) {
+        self.canceller.cancel();
+    }
+}
+
+impl Iterator for ScanHandle {
+    type Item = ScanEvent;
+
+    fn next(&mut self) -> Option<ScanEvent> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for ScanHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Cancels a scan, see ScanHandle::canceller().
+#[derive(Clone)]
+pub struct ScanCanceller {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ScanCanceller {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
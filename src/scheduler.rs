@@ -0,0 +1,584 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+use crate::Instance;
+use crate::vlm::{Vlm, VlmError};
+
+// Version of the schedule file format.
+const FORMAT_VERSION: u32 = 1;
+
+// Bound of the search of the next occurrence of a Cron, enough for any valid date.
+const MAX_CRON_STEPS: usize = 100_000;
+
+// Maximum number of days of each month.
+const MONTH_DAYS: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Error of Cron::from_str().
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseCronError(String);
+
+impl fmt::Display for ParseCronError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid cron expression: {}", self.0)
+    }
+}
+
+impl error::Error for ParseCronError {}
+
+/// A recurrence in the cron format: "minute hour day-of-month month day-of-week".
+///
+/// Each field is "*", a value, a range "a-b" or a list "a,b", optionally with a step "*/15".
+/// Days of the week go from 0 (Sunday) to 6, 7 is also Sunday.
+/// As in cron, if both the day of the month and the day of the week are restricted, either matches.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cron {
+    spec: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// Every day at hour:minute.
+    /// Returns an error if the hour is not between 0 and 23 or the minute between 0 and 59.
+    pub fn daily(hour: u32, minute: u32) -> Result<Cron, ParseCronError> {
+        format!("{} {} * * *", minute, hour).parse()
+    }
+
+    /// Returns the first occurrence at or after `minute`, in minutes since the epoch.
+    fn next(&self, minute: i64) -> Option<i64> {
+        let mut t = minute;
+        for _ in 0..MAX_CRON_STEPS {
+            let day = t.div_euclid(1440);
+            let (year, month, mday) = civil_from_days(day);
+            if !has(self.months, month) {
+                let (y, m) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                t = days_from_civil(y, m, 1) * 1440;
+                continue;
+            }
+            if !self.day_matches(day, mday) {
+                t = (day + 1) * 1440;
+                continue;
+            }
+            let minute_of_day = t.rem_euclid(1440);
+            if !has(self.hours, (minute_of_day / 60) as u32) {
+                t = (t.div_euclid(60) + 1) * 60;
+                continue;
+            }
+            if !has(self.minutes, (minute_of_day % 60) as u32) {
+                t += 1;
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    // Whether a day of the month exists in one of the months, February 29 included.
+    fn has_possible_day(&self) -> bool {
+        (1..=12).filter(|&month| has(self.months, month))
+            .any(|month| (1..=MONTH_DAYS[month as usize - 1]).any(|day| has(self.days, day)))
+    }
+
+    fn day_matches(&self, day: i64, mday: u32) -> bool {
+        let weekday = (day + 4).rem_euclid(7) as u32;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => has(self.days, mday),
+            (true, false) => has(self.weekdays, weekday),
+            (false, false) => has(self.days, mday) || has(self.weekdays, weekday),
+        }
+    }
+}
+
+impl FromStr for Cron {
+    type Err = ParseCronError;
+
+    fn from_str(s: &str) -> Result<Cron, ParseCronError> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(ParseCronError(s.to_owned()));
+        }
+        let field = |i: usize, min, max| parse_field(fields[i], min, max).ok_or_else(|| ParseCronError(s.to_owned()));
+
+        let weekdays = field(4, 0, 7)?;
+        let cron = Cron {
+            spec: fields.join(" "),
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            // 7 is Sunday too.
+            weekdays: (weekdays | (weekdays >> 7)) & 0x7f,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        };
+        // Only the day of the month restricts the dates, which may never occur, such as "0 0 31 2 *".
+        if !cron.any_day && cron.any_weekday && !cron.has_possible_day() {
+            return Err(ParseCronError(s.to_owned()));
+        }
+        Ok(cron)
+    }
+}
+
+impl TryFrom<String> for Cron {
+    type Error = ParseCronError;
+
+    fn try_from(s: String) -> Result<Cron, ParseCronError> {
+        s.parse()
+    }
+}
+
+impl From<Cron> for String {
+    fn from(cron: Cron) -> String {
+        cron.spec
+    }
+}
+
+impl fmt::Display for Cron {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+// Parses a cron field into a bit set of values.
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|&step| step > 0)?),
+            None => (part, 1),
+        };
+        let (first, last) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (a.parse().ok()?, b.parse().ok()?)
+        } else {
+            let value = range.parse().ok()?;
+            (value, if part.contains('/') { max } else { value })
+        };
+        if first < min || last > max || first > last {
+            return None;
+        }
+        for value in (first..=last).step_by(step) {
+            set |= 1 << value;
+        }
+    }
+    Some(set)
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+// Days since the epoch of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// Date of the proleptic Gregorian calendar from days since the epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    (yoe + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+/// A program of a channel: `input` is played for `minutes` from each occurrence of `start`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScheduleSlot {
+    pub start: Cron,
+    pub minutes: u32,
+    pub input: String,
+    /// Position to seek to when the input is switched, between 0 and 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seek: Option<f32>,
+}
+
+/// The programs of a VLM broadcast.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ChannelSchedule {
+    /// Name of the VLM broadcast, which must exist.
+    pub media: String,
+    /// Input played in loop outside of the slots. Without filler, the broadcast is stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filler: Option<String>,
+    pub slots: Vec<ScheduleSlot>,
+}
+
+/// What a channel plays at some time.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Program {
+    Slot { input: String, seek: Option<f32> },
+    Filler(String),
+    Stopped,
+}
+
+impl Program {
+    /// Returns the input played, or None if the broadcast is stopped.
+    pub fn input(&self) -> Option<&str> {
+        match *self {
+            Program::Slot{ ref input, .. } | Program::Filler(ref input) => Some(input),
+            Program::Stopped => None,
+        }
+    }
+}
+
+impl ChannelSchedule {
+    pub fn new(media: &str) -> ChannelSchedule {
+        ChannelSchedule{ media: media.to_owned(), filler: None, slots: Vec::new() }
+    }
+
+    /// Add a slot playing `input` for `minutes` from each occurrence of `start`.
+    pub fn slot(mut self, start: Cron, minutes: u32, input: &str) -> ChannelSchedule {
+        self.slots.push(ScheduleSlot{ start, minutes, input: input.to_owned(), seek: None });
+        self
+    }
+
+    /// Set the input played in loop outside of the slots.
+    pub fn filler(mut self, input: &str) -> ChannelSchedule {
+        self.filler = Some(input.to_owned());
+        self
+    }
+
+    // Returns the program at `minute` (local minutes since the epoch).
+    // When slots overlap, the slot which started last is played.
+    fn program_at(&self, minute: i64) -> Program {
+        let mut current: Option<(i64, &ScheduleSlot)> = None;
+        for slot in self.slots.iter().filter(|slot| slot.minutes > 0) {
+            let mut start = match slot.start.next(minute - slot.minutes as i64 + 1) {
+                Some(start) if start <= minute => start,
+                _ => continue,
+            };
+            while let Some(next) = slot.start.next(start + 1).filter(|&next| next <= minute) {
+                start = next;
+            }
+            if current.is_none_or(|(s, _)| start >= s) {
+                current = Some((start, slot));
+            }
+        }
+
+        match (current, &self.filler) {
+            (Some((_, slot)), _) => Program::Slot{ input: slot.input.clone(), seek: slot.seek },
+            (None, Some(filler)) => Program::Filler(filler.clone()),
+            (None, None) => Program::Stopped,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScheduleFile {
+    version: u32,
+    #[serde(flatten)]
+    schedule: Schedule,
+}
+
+/// The programs of several broadcasts, stored as a JSON file.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Offset of the wall-clock times from UTC (in minutes).
+    /// Daylight saving time changes are not applied.
+    #[serde(default)]
+    pub utc_offset: i32,
+    pub channels: Vec<ChannelSchedule>,
+}
+
+impl Schedule {
+    pub fn new(utc_offset: i32) -> Schedule {
+        Schedule{ utc_offset, channels: Vec::new() }
+    }
+
+    pub fn channel(mut self, channel: ChannelSchedule) -> Schedule {
+        self.channels.push(channel);
+        self
+    }
+
+    /// Read a schedule file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Schedule> {
+        let file: ScheduleFile = serde_json::from_slice(&fs::read(path)?)?;
+        if file.version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported schedule version {}", file.version)));
+        }
+        Ok(file.schedule)
+    }
+
+    /// Write the schedule file. The file is replaced atomically.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let file = ScheduleFile{ version: FORMAT_VERSION, schedule: self.clone() };
+        let data = serde_json::to_vec_pretty(&file)?;
+
+        let mut tmp = path.to_owned().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Returns the program of each channel at `time`.
+    pub fn programs_at(&self, time: SystemTime) -> Vec<(&str, Program)> {
+        let minute = self.local_minute(time);
+        self.channels.iter().map(|channel| (channel.media.as_str(), channel.program_at(minute))).collect()
+    }
+
+    /// Returns the program changes between `from` and `until`, starting with the programs at `from`.
+    pub fn timeline(&self, from: SystemTime, until: SystemTime) -> Vec<TimelineEntry> {
+        let mut entries = Vec::new();
+        let mut current: Vec<Option<Program>> = vec![None; self.channels.len()];
+
+        let (first, last) = (self.local_minute(from), self.local_minute(until));
+        for minute in first..=last {
+            for (channel, current) in self.channels.iter().zip(current.iter_mut()) {
+                let program = channel.program_at(minute);
+                if current.as_ref() != Some(&program) {
+                    entries.push(TimelineEntry {
+                        time: self.system_time(minute).max(from),
+                        local_minute: minute,
+                        media: channel.media.clone(),
+                        program: program.clone(),
+                    });
+                    *current = Some(program);
+                }
+            }
+        }
+        entries
+    }
+
+    fn local_minute(&self, time: SystemTime) -> i64 {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        (secs + self.utc_offset as i64 * 60).div_euclid(60)
+    }
+
+    fn system_time(&self, local_minute: i64) -> SystemTime {
+        let secs = (local_minute - self.utc_offset as i64) * 60;
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(-secs as u64)
+        }
+    }
+}
+
+/// A program change of Schedule::timeline().
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimelineEntry {
+    pub time: SystemTime,
+    local_minute: i64,
+    pub media: String,
+    pub program: Program,
+}
+
+impl fmt::Display for TimelineEntry {
+    /// Formats the entry as "YYYY-MM-DD HH:MM media: program", in the schedule's wall-clock time.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.local_minute.div_euclid(1440));
+        let minute_of_day = self.local_minute.rem_euclid(1440);
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02} {}: ",
+               year, month, day, minute_of_day / 60, minute_of_day % 60, self.media)?;
+        match self.program {
+            Program::Slot{ ref input, .. } => write!(f, "{}", input),
+            Program::Filler(ref input) => write!(f, "{} (filler)", input),
+            Program::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+/// Source of the current time of a Scheduler, which can be replaced in tests.
+pub trait Clock: Send {
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A program change applied by the Scheduler.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScheduledChange {
+    pub time: SystemTime,
+    pub media: String,
+    pub program: Program,
+    /// Result of the VLM calls, always Ok in dry-run mode.
+    pub result: Result<(), VlmError>,
+}
+
+/// Switches the inputs of VLM broadcasts according to a Schedule.
+pub struct Scheduler<V: Vlm = Instance> {
+    vlm: V,
+    schedule: Schedule,
+    clock: Box<dyn Clock>,
+    dry_run: bool,
+    current: HashMap<String, Program>,
+}
+
+impl<V: Vlm> Scheduler<V> {
+    pub fn new(vlm: V, schedule: Schedule) -> Scheduler<V> {
+        Scheduler {
+            vlm,
+            schedule,
+            clock: Box::new(SystemClock),
+            dry_run: false,
+            current: HashMap::new(),
+        }
+    }
+
+    /// Replace the system clock.
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Scheduler<V> {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// In dry-run mode, the changes are reported but the broadcasts are not touched.
+    pub fn dry_run(mut self, dry_run: bool) -> Scheduler<V> {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Write the timeline of the next `duration` to `out`, one entry per line.
+    pub fn print_timeline<W: io::Write>(&self, duration: Duration, out: &mut W) -> io::Result<()> {
+        let now = self.clock.now();
+        for entry in self.schedule.timeline(now, now + duration) {
+            writeln!(out, "{}", entry)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the programs of the current time, and returns the changes.
+    pub fn tick(&mut self) -> Vec<ScheduledChange> {
+        let time = self.clock.now();
+        let mut changes = Vec::new();
+
+        for (media, program) in self.schedule.programs_at(time) {
+            if self.current.get(media) == Some(&program) {
+                continue;
+            }
+            let result = if self.dry_run { Ok(()) } else { self.switch(media, &program) };
+            if result.is_ok() {
+                self.current.insert(media.to_owned(), program.clone());
+            }
+            changes.push(ScheduledChange{ time, media: media.to_owned(), program, result });
+        }
+        changes
+    }
+
+    fn switch(&self, media: &str, program: &Program) -> Result<(), VlmError> {
+        let instance = &self.vlm;
+        match *program {
+            Program::Slot{ ref input, seek } => {
+                instance.set_input(media, input)?;
+                instance.set_loop(media, false)?;
+                play(instance, media)?;
+                if let Some(seek) = seek {
+                    instance.seek_media(media, seek)?;
+                }
+                Ok(())
+            },
+            Program::Filler(ref input) => {
+                instance.set_input(media, input)?;
+                instance.set_loop(media, true)?;
+                play(instance, media)
+            },
+            Program::Stopped => instance.stop_media(media),
+        }
+    }
+
+    /// Run the scheduler in a background thread, checking the schedule every `interval`.
+    /// The returned handle yields the changes, and stops the scheduler when dropped.
+    pub fn start(mut self, interval: Duration) -> SchedulerHandle
+        where V: Send + 'static
+    {
+        let (tx, rx) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                for change in self.tick() {
+                    let _ = tx.send(change);
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        SchedulerHandle{ rx, stopped }
+    }
+}
+
+// Restarts the broadcast from its new input.
+fn play<V: Vlm>(instance: &V, media: &str) -> Result<(), VlmError> {
+    let _ = instance.stop_media(media);
+    instance.play_media(media)
+}
+
+/// Handle to a running Scheduler. Iterating over it yields the changes until it is stopped.
+pub struct SchedulerHandle {
+    rx: Receiver<ScheduledChange>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl SchedulerHandle {
+    /// Stop the scheduler. The broadcasts are left as they are.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Iterator for SchedulerHandle {
+    type Item = ScheduledChange;
+
+    fn next(&mut self) -> Option<ScheduledChange> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for SchedulerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,750 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Options which are not modelled, as (name, value) pairs. They are kept in order.
+pub type SoutExtraOptions = Vec<(String, Option<String>)>;
+
+/// Error returned when a stream output chain cannot be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseSoutError(String);
+
+impl fmt::Display for ParseSoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid stream output chain: {}", self.0)
+    }
+}
+
+impl error::Error for ParseSoutError {}
+
+/// A stream output chain, e.g. "#transcode{vcodec=h264,vb=800}:std{access=file,mux=mp4,dst=out.mp4}".
+///
+/// It is rendered with Display, so it can be given to BroadcastConfig::output(),
+/// and parsed back with FromStr.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Sout {
+    modules: Vec<SoutModule>,
+}
+
+/// A module of a stream output chain.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SoutModule {
+    Transcode(TranscodeOptions),
+    Std(StdOptions),
+    Duplicate(DuplicateOptions),
+    Display(DisplayOptions),
+    Es(EsOptions),
+    Rtp(RtpOptions),
+    Http(HttpOptions),
+    /// Any other module, with its options.
+    Other { name: String, options: SoutExtraOptions },
+}
+
+/// Options of the transcode module. Bitrates are in kb/s.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TranscodeOptions {
+    pub vcodec: Option<String>,
+    pub vb: Option<u32>,
+    pub scale: Option<f32>,
+    pub fps: Option<f32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub acodec: Option<String>,
+    pub ab: Option<u32>,
+    pub channels: Option<u32>,
+    pub samplerate: Option<u32>,
+    pub extra: SoutExtraOptions,
+}
+
+/// Options of the std module, which writes to a file, http, udp...
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StdOptions {
+    pub access: Option<String>,
+    pub mux: Option<String>,
+    pub dst: Option<String>,
+    pub extra: SoutExtraOptions,
+}
+
+/// Options of the duplicate module, which sends the stream to several chains.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DuplicateOptions {
+    pub dst: Vec<DuplicateDst>,
+    pub extra: SoutExtraOptions,
+}
+
+/// A destination of the duplicate module.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DuplicateDst {
+    pub chain: Sout,
+    /// Selects the elementary streams, e.g. "video".
+    pub select: Option<String>,
+}
+
+/// Options of the display module, which plays the stream locally.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DisplayOptions {
+    pub extra: SoutExtraOptions,
+}
+
+/// Options of the es module, which outputs each elementary stream separately.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EsOptions {
+    pub access: Option<String>,
+    pub mux: Option<String>,
+    pub dst: Option<String>,
+    pub extra: SoutExtraOptions,
+}
+
+/// Options of the rtp module.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RtpOptions {
+    pub dst: Option<String>,
+    pub port: Option<u16>,
+    pub mux: Option<String>,
+    pub sdp: Option<String>,
+    pub name: Option<String>,
+    pub extra: SoutExtraOptions,
+}
+
+/// Options of the http module, a shortcut of std with the http access.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct HttpOptions {
+    pub mux: Option<String>,
+    pub dst: Option<String>,
+    pub extra: SoutExtraOptions,
+}
+
+impl Sout {
+    /// Create an empty chain.
+    pub fn new() -> Sout {
+        Sout::default()
+    }
+
+    /// Append a module to the chain.
+    pub fn module(mut self, module: SoutModule) -> Sout {
+        self.modules.push(module);
+        self
+    }
+
+    pub fn transcode(self, options: TranscodeOptions) -> Sout {
+        self.module(SoutModule::Transcode(options))
+    }
+
+    pub fn std(self, options: StdOptions) -> Sout {
+        self.module(SoutModule::Std(options))
+    }
+
+    pub fn duplicate(self, options: DuplicateOptions) -> Sout {
+        self.module(SoutModule::Duplicate(options))
+    }
+
+    pub fn display(self) -> Sout {
+        self.module(SoutModule::Display(DisplayOptions::default()))
+    }
+
+    pub fn es(self, options: EsOptions) -> Sout {
+        self.module(SoutModule::Es(options))
+    }
+
+    pub fn rtp(self, options: RtpOptions) -> Sout {
+        self.module(SoutModule::Rtp(options))
+    }
+
+    pub fn http(self, options: HttpOptions) -> Sout {
+        self.module(SoutModule::Http(options))
+    }
+
+    pub fn modules(&self) -> &[SoutModule] {
+        &self.modules
+    }
+
+    pub fn modules_mut(&mut self) -> &mut Vec<SoutModule> {
+        &mut self.modules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    // Writes the chain without the leading '#', as in the destinations of duplicate.
+    fn write_chain(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, module) in self.modules.iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            let (name, options) = module.options();
+            f.write_str(name)?;
+            if !options.is_empty() {
+                f.write_str("{")?;
+                for (j, (key, value)) in options.iter().enumerate() {
+                    if j > 0 {
+                        f.write_str(",")?;
+                    }
+                    f.write_str(key)?;
+                    match *value {
+                        Some(OptionValue::Text(ref value)) => write!(f, "={}", quote(value))?,
+                        Some(OptionValue::Chain(chain)) if !chain.is_empty() => {
+                            f.write_str("=")?;
+                            chain.write_chain(f)?;
+                        },
+                        Some(OptionValue::Chain(_)) => f.write_str("=\"\"")?,
+                        None => {},
+                    }
+                }
+                f.write_str("}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Sout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modules.is_empty() {
+            return Ok(());
+        }
+        f.write_str("#")?;
+        self.write_chain(f)
+    }
+}
+
+impl FromStr for Sout {
+    type Err = ParseSoutError;
+
+    /// Parse a chain, with or without the leading '#'.
+    fn from_str(s: &str) -> Result<Sout, ParseSoutError> {
+        let trimmed = s.trim();
+        let chain = trimmed.strip_prefix('#').unwrap_or(trimmed);
+        let mut sout = Sout::new();
+        if chain.is_empty() {
+            return Ok(sout);
+        }
+        for (name, options) in ChainParser::new(chain).parse().map_err(|msg| ParseSoutError(format!("{}: {}", msg, s)))? {
+            let module = SoutModule::from_options(name, options).map_err(|msg| ParseSoutError(format!("{}: {}", msg, s)))?;
+            sout.modules.push(module);
+        }
+        Ok(sout)
+    }
+}
+
+enum OptionValue<'a> {
+    Text(String),
+    Chain(&'a Sout),
+}
+
+// Appends an option if it is set.
+fn push<T: ToString>(options: &mut Vec<(String, Option<OptionValue<'_>>)>, key: &str, value: &Option<T>) {
+    if let Some(ref value) = *value {
+        options.push((key.to_owned(), Some(OptionValue::Text(value.to_string()))));
+    }
+}
+
+fn push_extra(options: &mut Vec<(String, Option<OptionValue<'_>>)>, extra: &SoutExtraOptions) {
+    for (key, value) in extra {
+        options.push((key.clone(), value.clone().map(OptionValue::Text)));
+    }
+}
+
+// Parses the value of a typed option.
+fn parse<T: FromStr>(key: &str, value: Option<String>) -> Result<Option<T>, String> {
+    match value {
+        Some(value) => value.parse().map(Some).map_err(|_| format!("invalid {} value {}", key, value)),
+        None => Err(format!("missing {} value", key)),
+    }
+}
+
+impl SoutModule {
+    /// Returns the name of the module, as in the chain.
+    pub fn name(&self) -> &str {
+        match *self {
+            SoutModule::Transcode(_) => "transcode",
+            SoutModule::Std(_) => "std",
+            SoutModule::Duplicate(_) => "duplicate",
+            SoutModule::Display(_) => "display",
+            SoutModule::Es(_) => "es",
+            SoutModule::Rtp(_) => "rtp",
+            SoutModule::Http(_) => "http",
+            SoutModule::Other { ref name, .. } => name,
+        }
+    }
+
+    fn options(&self) -> (&str, Vec<(String, Option<OptionValue<'_>>)>) {
+        let mut options = Vec::new();
+        match *self {
+            SoutModule::Transcode(ref o) => {
+                push(&mut options, "vcodec", &o.vcodec);
+                push(&mut options, "vb", &o.vb);
+                push(&mut options, "scale", &o.scale);
+                push(&mut options, "fps", &o.fps);
+                push(&mut options, "width", &o.width);
+                push(&mut options, "height", &o.height);
+                push(&mut options, "acodec", &o.acodec);
+                push(&mut options, "ab", &o.ab);
+                push(&mut options, "channels", &o.channels);
+                push(&mut options, "samplerate", &o.samplerate);
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Std(ref o) => {
+                push(&mut options, "access", &o.access);
+                push(&mut options, "mux", &o.mux);
+                push(&mut options, "dst", &o.dst);
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Duplicate(ref o) => {
+                for dst in &o.dst {
+                    options.push(("dst".to_owned(), Some(OptionValue::Chain(&dst.chain))));
+                    push(&mut options, "select", &dst.select);
+                }
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Display(ref o) => push_extra(&mut options, &o.extra),
+            SoutModule::Es(ref o) => {
+                push(&mut options, "access", &o.access);
+                push(&mut options, "mux", &o.mux);
+                push(&mut options, "dst", &o.dst);
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Rtp(ref o) => {
+                push(&mut options, "dst", &o.dst);
+                push(&mut options, "port", &o.port);
+                push(&mut options, "mux", &o.mux);
+                push(&mut options, "sdp", &o.sdp);
+                push(&mut options, "name", &o.name);
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Http(ref o) => {
+                push(&mut options, "mux", &o.mux);
+                push(&mut options, "dst", &o.dst);
+                push_extra(&mut options, &o.extra);
+            },
+            SoutModule::Other { options: ref other, .. } => push_extra(&mut options, other),
+        }
+        (self.name(), options)
+    }
+
+    fn from_options(name: String, options: SoutExtraOptions) -> Result<SoutModule, String> {
+        let module = match name.as_str() {
+            "transcode" => {
+                let mut o = TranscodeOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "vcodec" => o.vcodec = value,
+                        "vb" => o.vb = parse(&key, value)?,
+                        "scale" => o.scale = parse(&key, value)?,
+                        "fps" => o.fps = parse(&key, value)?,
+                        "width" => o.width = parse(&key, value)?,
+                        "height" => o.height = parse(&key, value)?,
+                        "acodec" => o.acodec = value,
+                        "ab" => o.ab = parse(&key, value)?,
+                        "channels" => o.channels = parse(&key, value)?,
+                        "samplerate" => o.samplerate = parse(&key, value)?,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Transcode(o)
+            },
+            "std" | "standard" => {
+                let mut o = StdOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "access" => o.access = value,
+                        "mux" => o.mux = value,
+                        "dst" => o.dst = value,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Std(o)
+            },
+            "duplicate" => {
+                let mut o = DuplicateOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "dst" => {
+                            let chain = value.unwrap_or_default().parse().map_err(|e: ParseSoutError| e.0)?;
+                            o.dst.push(DuplicateDst { chain, select: None });
+                        },
+                        // select applies to the previous destination.
+                        "select" if !o.dst.is_empty() => o.dst.last_mut().unwrap().select = value,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Duplicate(o)
+            },
+            "display" => SoutModule::Display(DisplayOptions { extra: options }),
+            "es" => {
+                let mut o = EsOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "access" => o.access = value,
+                        "mux" => o.mux = value,
+                        "dst" => o.dst = value,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Es(o)
+            },
+            "rtp" => {
+                let mut o = RtpOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "dst" => o.dst = value,
+                        "port" => o.port = parse(&key, value)?,
+                        "mux" => o.mux = value,
+                        "sdp" => o.sdp = value,
+                        "name" => o.name = value,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Rtp(o)
+            },
+            "http" => {
+                let mut o = HttpOptions::default();
+                for (key, value) in options {
+                    match key.as_str() {
+                        "mux" => o.mux = value,
+                        "dst" => o.dst = value,
+                        _ => o.extra.push((key, value)),
+                    }
+                }
+                SoutModule::Http(o)
+            },
+            _ => SoutModule::Other { name, options },
+        };
+        Ok(module)
+    }
+}
+
+impl TranscodeOptions {
+    pub fn new() -> TranscodeOptions {
+        TranscodeOptions::default()
+    }
+
+    /// Set the video codec, e.g. "h264".
+    pub fn vcodec(mut self, codec: &str) -> TranscodeOptions {
+        self.vcodec = Some(codec.to_owned());
+        self
+    }
+
+    /// Set the video bitrate in kb/s.
+    pub fn vb(mut self, bitrate: u32) -> TranscodeOptions {
+        self.vb = Some(bitrate);
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> TranscodeOptions {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn fps(mut self, fps: f32) -> TranscodeOptions {
+        self.fps = Some(fps);
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> TranscodeOptions {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
+
+    /// Set the audio codec, e.g. "mp4a".
+    pub fn acodec(mut self, codec: &str) -> TranscodeOptions {
+        self.acodec = Some(codec.to_owned());
+        self
+    }
+
+    /// Set the audio bitrate in kb/s.
+    pub fn ab(mut self, bitrate: u32) -> TranscodeOptions {
+        self.ab = Some(bitrate);
+        self
+    }
+
+    pub fn channels(mut self, channels: u32) -> TranscodeOptions {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn samplerate(mut self, samplerate: u32) -> TranscodeOptions {
+        self.samplerate = Some(samplerate);
+        self
+    }
+
+    /// Add an option which is not modelled.
+    pub fn option(mut self, name: &str, value: Option<&str>) -> TranscodeOptions {
+        self.extra.push((name.to_owned(), value.map(|v| v.to_owned())));
+        self
+    }
+}
+
+impl StdOptions {
+    pub fn new() -> StdOptions {
+        StdOptions::default()
+    }
+
+    /// Set the access, e.g. "file", "http" or "udp".
+    pub fn access(mut self, access: &str) -> StdOptions {
+        self.access = Some(access.to_owned());
+        self
+    }
+
+    /// Set the muxer, e.g. "ts" or "mp4".
+    pub fn mux(mut self, mux: &str) -> StdOptions {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    /// Set the destination, a path or an address.
+    pub fn dst(mut self, dst: &str) -> StdOptions {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+
+    /// Add an option which is not modelled.
+    pub fn option(mut self, name: &str, value: Option<&str>) -> StdOptions {
+        self.extra.push((name.to_owned(), value.map(|v| v.to_owned())));
+        self
+    }
+}
+
+impl DuplicateOptions {
+    pub fn new() -> DuplicateOptions {
+        DuplicateOptions::default()
+    }
+
+    /// Add a destination chain.
+    pub fn dst(mut self, chain: Sout) -> DuplicateOptions {
+        self.dst.push(DuplicateDst { chain, select: None });
+        self
+    }
+
+    /// Add a destination chain which receives the selected elementary streams.
+    pub fn dst_select(mut self, chain: Sout, select: &str) -> DuplicateOptions {
+        self.dst.push(DuplicateDst { chain, select: Some(select.to_owned()) });
+        self
+    }
+}
+
+impl EsOptions {
+    pub fn new() -> EsOptions {
+        EsOptions::default()
+    }
+
+    pub fn access(mut self, access: &str) -> EsOptions {
+        self.access = Some(access.to_owned());
+        self
+    }
+
+    pub fn mux(mut self, mux: &str) -> EsOptions {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    /// Set the destination. "$i" is replaced by the stream id and "$c" by the codec.
+    pub fn dst(mut self, dst: &str) -> EsOptions {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+}
+
+impl RtpOptions {
+    pub fn new() -> RtpOptions {
+        RtpOptions::default()
+    }
+
+    pub fn dst(mut self, dst: &str) -> RtpOptions {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> RtpOptions {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn mux(mut self, mux: &str) -> RtpOptions {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    /// Set the SDP announce, e.g. "rtsp://:5554/stream".
+    pub fn sdp(mut self, sdp: &str) -> RtpOptions {
+        self.sdp = Some(sdp.to_owned());
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> RtpOptions {
+        self.name = Some(name.to_owned());
+        self
+    }
+}
+
+impl HttpOptions {
+    pub fn new() -> HttpOptions {
+        HttpOptions::default()
+    }
+
+    pub fn mux(mut self, mux: &str) -> HttpOptions {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    /// Set the address to listen to, e.g. ":8080/stream".
+    pub fn dst(mut self, dst: &str) -> HttpOptions {
+        self.dst = Some(dst.to_owned());
+        self
+    }
+}
+
+// Quotes a value if needed, as the option parser of VLC reads it.
+fn quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || ",{}\"'\\".contains(c)) {
+        return value.to_owned();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Parser of chains, "module{key=value,key}:module".
+struct ChainParser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> ChainParser<'a> {
+    fn new(s: &'a str) -> ChainParser<'a> {
+        ChainParser { s, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    // Reads until one of the delimiters.
+    fn word(&mut self, delimiters: &str) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !delimiters.contains(c)) {
+            self.bump();
+        }
+        self.s[start..self.pos].trim()
+    }
+
+    fn parse(mut self) -> Result<Vec<(String, SoutExtraOptions)>, String> {
+        let mut modules = Vec::new();
+        loop {
+            let name = self.word("{:");
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(format!("invalid module name \"{}\"", name));
+            }
+            let options = if self.peek() == Some('{') {
+                self.bump();
+                self.options()?
+            } else {
+                Vec::new()
+            };
+            modules.push((name.to_owned(), options));
+
+            self.skip_spaces();
+            match self.bump() {
+                Some(':') => {},
+                None => return Ok(modules),
+                Some(c) => return Err(format!("unexpected '{}'", c)),
+            }
+        }
+    }
+
+    // Reads the options until the closing brace.
+    fn options(&mut self) -> Result<SoutExtraOptions, String> {
+        let mut options = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some('}') {
+                self.bump();
+                return Ok(options);
+            }
+            let key = self.word("=,}");
+            if key.is_empty() {
+                return Err("missing option name".to_owned());
+            }
+            let value = if self.peek() == Some('=') {
+                self.bump();
+                Some(self.value()?)
+            } else {
+                None
+            };
+            options.push((key.to_owned(), value));
+
+            self.skip_spaces();
+            match self.bump() {
+                Some(',') => {},
+                Some('}') => return Ok(options),
+                Some(c) => return Err(format!("unexpected '{}'", c)),
+                None => return Err("missing '}'".to_owned()),
+            }
+        }
+    }
+
+    // Reads a quoted value, or a raw value which may contain balanced braces.
+    fn value(&mut self) -> Result<String, String> {
+        self.skip_spaces();
+        match self.peek() {
+            Some(q) if q == '"' || q == '\'' => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('\\') => value.push(self.bump().ok_or("unterminated quote")?),
+                        Some(c) if c == q => return Ok(value),
+                        Some(c) => value.push(c),
+                        None => return Err("unterminated quote".to_owned()),
+                    }
+                }
+            },
+            _ => {
+                let start = self.pos;
+                let mut depth = 0;
+                let mut quote = None;
+                while let Some(c) = self.peek() {
+                    match (quote, c) {
+                        (Some(_), '\\') => { self.bump(); },
+                        (Some(q), c) if c == q => quote = None,
+                        (Some(_), _) => {},
+                        (None, '"') | (None, '\'') => quote = Some(c),
+                        (None, '{') => depth += 1,
+                        (None, '}') | (None, ',') if depth == 0 => break,
+                        (None, '}') => depth -= 1,
+                        (None, _) => {},
+                    }
+                    self.bump();
+                }
+                if quote.is_some() || depth != 0 {
+                    return Err("unbalanced value".to_owned());
+                }
+                Ok(self.s[start..self.pos].trim_end().to_owned())
+            },
+        }
+    }
+}
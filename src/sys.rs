@@ -7,7 +7,7 @@
 #[link(name = "vlc")]
 extern "C" {}
 
-use libc::{c_void, c_int, c_uint, c_char, c_float, uintptr_t, FILE};
+use libc::{c_void, c_int, c_uint, c_char, c_uchar, c_float, uintptr_t, ssize_t, FILE};
 
 pub type c_bool = u8;
 
@@ -109,6 +109,7 @@ pub const libvlc_media_option_trusted: u32 = 0x2;
 pub const libvlc_media_option_unique: u32 = 0x100;
 
 pub use crate::enums::TrackType as libvlc_track_type_t;
+pub use crate::enums::ParseFlag as libvlc_media_parse_flag_t;
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -238,6 +239,12 @@ impl libvlc_media_track_t {
     }
 }
 
+pub type libvlc_media_open_cb = Option<unsafe extern "C" fn(
+    *mut c_void, *mut *mut c_void, *mut u64) -> c_int>;
+pub type libvlc_media_read_cb = Option<unsafe extern "C" fn(*mut c_void, *mut c_uchar, usize) -> ssize_t>;
+pub type libvlc_media_seek_cb = Option<unsafe extern "C" fn(*mut c_void, u64) -> c_int>;
+pub type libvlc_media_close_cb = Option<unsafe extern "C" fn(*mut c_void)>;
+
 extern "C" {
     pub fn libvlc_media_new_location(p_instance: *mut libvlc_instance_t, psz_mrl: *const c_char)
                                      -> *mut libvlc_media_t;
@@ -245,8 +252,12 @@ extern "C" {
                                  -> *mut libvlc_media_t;
     pub fn libvlc_media_new_fd(p_instance: *mut libvlc_instance_t, fd: c_int)
                                -> *mut libvlc_media_t;
-    pub fn libvlc_media_as_node(p_instance: *mut libvlc_instance_t, psz_name: *const c_char)
-                                -> *mut libvlc_media_t;
+    pub fn libvlc_media_new_callbacks(
+        p_instance: *mut libvlc_instance_t, open_cb: libvlc_media_open_cb,
+        read_cb: libvlc_media_read_cb, seek_cb: libvlc_media_seek_cb,
+        close_cb: libvlc_media_close_cb, opaque: *mut c_void) -> *mut libvlc_media_t;
+    pub fn libvlc_media_new_as_node(p_instance: *mut libvlc_instance_t, psz_name: *const c_char)
+                                    -> *mut libvlc_media_t;
     pub fn libvlc_media_add_option(p_md: *mut libvlc_media_t, psz_options: *const c_char);
     pub fn libvlc_media_add_option_flag(
         p_md: *mut libvlc_media_t, psz_options: *const c_char, i_flags: c_uint);
@@ -270,6 +281,12 @@ extern "C" {
     pub fn libvlc_media_parse(p_md: *mut libvlc_media_t);
     pub fn libvlc_media_parse_async(p_md: *mut libvlc_media_t);
     pub fn libvlc_media_is_parsed(p_md: *mut libvlc_media_t) -> c_int;
+    // parse_flag is a combination of libvlc_media_parse_flag_t values.
+    pub fn libvlc_media_parse_with_options(
+        p_md: *mut libvlc_media_t, parse_flag: c_int, timeout: c_int) -> c_int;
+    pub fn libvlc_media_parse_stop(p_md: *mut libvlc_media_t);
+    // Returns 0 if the media has not been parsed yet, else a libvlc_media_parsed_status_t value.
+    pub fn libvlc_media_get_parsed_status(p_md: *mut libvlc_media_t) -> c_int;
     pub fn libvlc_media_set_user_data(p_md: *mut libvlc_media_t,
                                       p_new_user_data: *mut c_void);
     pub fn libvlc_media_get_user_data(p_md: *mut libvlc_media_t) -> *mut c_void;
@@ -637,6 +654,7 @@ pub mod libvlc_event_t_types {
     #[repr(C)]
     #[derive(Clone, Copy)]
     pub struct media_parsed_changed {
+        // A libvlc_media_parsed_status_t value, read as an integer since it comes from C.
         pub new_status: c_int,
     }
     #[repr(C)]
@@ -773,6 +791,41 @@ extern "C" {
         p_ml: *mut libvlc_media_list_t) -> *mut libvlc_event_manager_t;
 }
 
+// From libvlc_media_list_player.h
+
+pub enum libvlc_media_list_player_t {}
+
+pub use crate::enums::PlaybackMode as libvlc_playback_mode_t;
+
+extern "C" {
+    pub fn libvlc_media_list_player_new(
+        p_instance: *mut libvlc_instance_t) -> *mut libvlc_media_list_player_t;
+    pub fn libvlc_media_list_player_release(p_mlp: *mut libvlc_media_list_player_t);
+    pub fn libvlc_media_list_player_retain(p_mlp: *mut libvlc_media_list_player_t);
+    pub fn libvlc_media_list_player_event_manager(
+        p_mlp: *mut libvlc_media_list_player_t) -> *mut libvlc_event_manager_t;
+    pub fn libvlc_media_list_player_set_media_player(
+        p_mlp: *mut libvlc_media_list_player_t, p_mi: *mut libvlc_media_player_t);
+    pub fn libvlc_media_list_player_get_media_player(
+        p_mlp: *mut libvlc_media_list_player_t) -> *mut libvlc_media_player_t;
+    pub fn libvlc_media_list_player_set_media_list(
+        p_mlp: *mut libvlc_media_list_player_t, p_mlist: *mut libvlc_media_list_t);
+    pub fn libvlc_media_list_player_play(p_mlp: *mut libvlc_media_list_player_t);
+    pub fn libvlc_media_list_player_pause(p_mlp: *mut libvlc_media_list_player_t);
+    pub fn libvlc_media_list_player_set_pause(p_mlp: *mut libvlc_media_list_player_t, do_pause: c_int);
+    pub fn libvlc_media_list_player_is_playing(p_mlp: *mut libvlc_media_list_player_t) -> c_int;
+    pub fn libvlc_media_list_player_get_state(p_mlp: *mut libvlc_media_list_player_t) -> libvlc_state_t;
+    pub fn libvlc_media_list_player_play_item_at_index(
+        p_mlp: *mut libvlc_media_list_player_t, i_index: c_int) -> c_int;
+    pub fn libvlc_media_list_player_play_item(
+        p_mlp: *mut libvlc_media_list_player_t, p_md: *mut libvlc_media_t) -> c_int;
+    pub fn libvlc_media_list_player_stop(p_mlp: *mut libvlc_media_list_player_t);
+    pub fn libvlc_media_list_player_next(p_mlp: *mut libvlc_media_list_player_t) -> c_int;
+    pub fn libvlc_media_list_player_previous(p_mlp: *mut libvlc_media_list_player_t) -> c_int;
+    pub fn libvlc_media_list_player_set_playback_mode(
+        p_mlp: *mut libvlc_media_list_player_t, e_mode: libvlc_playback_mode_t);
+}
+
 // From libvlc_media_library.h
 
 pub enum libvlc_media_library_t {}
@@ -790,7 +843,27 @@ extern "C" {
 
 pub enum libvlc_media_discoverer_t {}
 
+pub use crate::enums::MediaDiscovererCategory as libvlc_media_discoverer_category_t;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct libvlc_media_discoverer_description_t {
+    pub psz_name: *mut c_char,
+    pub psz_longname: *mut c_char,
+    // Read as an integer, libvlc may return a category unknown to MediaDiscovererCategory.
+    pub i_cat: c_int,
+}
+
 extern "C" {
+    pub fn libvlc_media_discoverer_new(
+        p_inst: *mut libvlc_instance_t, psz_name: *const c_char) -> *mut libvlc_media_discoverer_t;
+    pub fn libvlc_media_discoverer_start(p_mdis: *mut libvlc_media_discoverer_t) -> c_int;
+    pub fn libvlc_media_discoverer_stop(p_mdis: *mut libvlc_media_discoverer_t);
+    pub fn libvlc_media_discoverer_list_get(
+        p_inst: *mut libvlc_instance_t, i_cat: libvlc_media_discoverer_category_t,
+        ppp_services: *mut *mut *mut libvlc_media_discoverer_description_t) -> usize;
+    pub fn libvlc_media_discoverer_list_release(
+        pp_services: *mut *mut libvlc_media_discoverer_description_t, i_count: usize);
     pub fn libvlc_media_discoverer_new_from_name(
         p_inst: *mut libvlc_instance_t, psz_name: *const c_char) -> *mut libvlc_media_discoverer_t;
     pub fn libvlc_media_discoverer_release(p_mdis: *mut libvlc_media_discoverer_t);
@@ -0,0 +1,376 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use libc::c_void;
+use crate::sys;
+use crate::{Instance, Media, MediaOptions, MediaPlayer};
+use crate::core::{Event, conv_event};
+use crate::enums::EventType;
+use crate::sout::{Sout, SoutModule, StdOptions, TranscodeOptions};
+
+/// Arguments of an instance for transcoding, see Transcoder::instance().
+pub const TRANSCODER_ARGS: &[&str] = &["--quiet", "--vout=dummy", "--aout=dummy", "--no-video-title-show"];
+
+// Interval at which a waiting handle checks for cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+const PLAYER_EVENTS: &[EventType] = &[
+    EventType::MediaPlayerPositionChanged,
+    EventType::MediaPlayerTimeChanged,
+    EventType::MediaPlayerEndReached,
+    EventType::MediaPlayerEncounteredError,
+    EventType::MediaPlayerStopped,
+];
+
+/// Common conversion profiles.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TranscodePreset {
+    /// H.264 video and AAC audio in MP4.
+    H264Mp4,
+    /// Opus audio in WebM.
+    OpusWebm,
+    /// MP3 audio.
+    Mp3,
+    /// FLAC audio.
+    Flac,
+}
+
+impl TranscodePreset {
+    /// Returns the chain of the preset, without destination.
+    pub fn sout(self) -> Sout {
+        let (transcode, mux) = match self {
+            TranscodePreset::H264Mp4 => (TranscodeOptions::new().vcodec("h264").vb(2000)
+                                         .acodec("mp4a").ab(160).channels(2).samplerate(48000), "mp4"),
+            TranscodePreset::OpusWebm => (TranscodeOptions::new().acodec("opus").ab(128).channels(2).samplerate(48000), "webm"),
+            TranscodePreset::Mp3 => (TranscodeOptions::new().acodec("mp3").ab(192).channels(2).samplerate(44100), "raw"),
+            TranscodePreset::Flac => (TranscodeOptions::new().acodec("flac").channels(2).samplerate(44100), "raw"),
+        };
+        Sout::new().transcode(transcode).std(StdOptions::new().mux(mux))
+    }
+
+    /// Returns the usual file extension of the output.
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranscodePreset::H264Mp4 => "mp4",
+            TranscodePreset::OpusWebm => "webm",
+            TranscodePreset::Mp3 => "mp3",
+            TranscodePreset::Flac => "flac",
+        }
+    }
+
+    /// Returns true if the video is dropped.
+    pub fn is_audio_only(self) -> bool {
+        self != TranscodePreset::H264Mp4
+    }
+}
+
+/// Error of a transcoding.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TranscodeError {
+    /// The media player could not be created or started, or the previous output could not be removed.
+    Start,
+    /// The media reads its data from callbacks, and cannot be opened again for the transcoding.
+    UnsupportedMedia,
+    /// libvlc reported an error, with the libvlc message if any.
+    Failed(Option<String>),
+    /// The transcoding was cancelled.
+    Cancelled,
+    /// The transcoding ended without writing the output file.
+    NoOutput,
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TranscodeError::Start => write!(f, "cannot start the transcoding"),
+            TranscodeError::UnsupportedMedia => write!(f, "cannot transcode a media read from callbacks"),
+            TranscodeError::Failed(Some(ref msg)) => write!(f, "transcoding failed: {}", msg),
+            TranscodeError::Failed(None) => write!(f, "transcoding failed"),
+            TranscodeError::Cancelled => write!(f, "transcoding cancelled"),
+            TranscodeError::NoOutput => write!(f, "no output was written"),
+        }
+    }
+}
+
+impl error::Error for TranscodeError {}
+
+/// Result of a successful transcoding.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TranscodeOutput {
+    pub path: PathBuf,
+    /// Size of the output file in bytes.
+    pub size: u64,
+    pub elapsed: Duration,
+}
+
+/// Progress of a transcoding.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TranscodeProgress {
+    /// Position in the input, between 0.0 and 1.0.
+    pub position: f32,
+    /// Time in the input (in ms).
+    pub time: Option<i64>,
+}
+
+/// Converts a media to a file with a stream output chain.
+///
+/// The media is played without display, as fast as the file output allows.
+/// Use an instance created with Transcoder::instance() so that no window or audio device is opened.
+#[derive(Clone)]
+pub struct Transcoder {
+    instance: Instance,
+    media: Media,
+    profile: Sout,
+    output: PathBuf,
+    options: MediaOptions,
+}
+
+impl Transcoder {
+    /// Create an instance with TRANSCODER_ARGS.
+    pub fn instance() -> Option<Instance> {
+        Instance::with_args(Some(TRANSCODER_ARGS.iter().map(|arg| arg.to_string()).collect()))
+    }
+
+    /// Create a transcoder writing to output with the profile.
+    /// The output is set as the destination of the last std module, or of an appended one.
+    pub fn new<P: AsRef<Path>>(instance: &Instance, media: &Media, profile: Sout, output: P) -> Transcoder {
+        Transcoder {
+            instance: instance.clone(),
+            media: media.clone(),
+            profile,
+            output: output.as_ref().to_path_buf(),
+            options: MediaOptions::new(),
+        }
+    }
+
+    /// Create a transcoder with a preset.
+    pub fn with_preset<P: AsRef<Path>>(instance: &Instance, media: &Media, preset: TranscodePreset, output: P) -> Transcoder {
+        let transcoder = Transcoder::new(instance, media, preset.sout(), output);
+        if preset.is_audio_only() {
+            transcoder.options(MediaOptions::new().option(":no-sout-video", true).option(":no-sout-spu", true))
+        } else {
+            transcoder
+        }
+    }
+
+    /// Add options of the input, e.g. MediaOptions::start_time().
+    pub fn options(mut self, options: MediaOptions) -> Transcoder {
+        for option in options.options() {
+            self.options = self.options.option_flag(&option.option, option.trusted, option.unique);
+        }
+        self
+    }
+
+    /// Returns the chain with the output as destination.
+    pub fn sout(&self) -> Sout {
+        let mut sout = self.profile.clone();
+        let dst = self.output.to_string_lossy().into_owned();
+        match sout.modules_mut().last_mut() {
+            Some(SoutModule::Std(std)) => {
+                std.access.get_or_insert_with(|| "file".to_owned());
+                std.dst = Some(dst);
+            },
+            _ => sout = sout.std(StdOptions::new().access("file").dst(&dst)),
+        }
+        sout
+    }
+
+    /// Start the transcoding. The returned handle yields the progress.
+    /// An existing output file is removed first.
+    pub fn start(&self) -> Result<TranscodeHandle, TranscodeError> {
+        let media = self.input()?;
+        media.add_options(&self.options);
+        media.add_options(&MediaOptions::new()
+            .sout(&self.sout().to_string()).trusted()
+            // Do not drop frames to keep up with real time.
+            .option(":no-sout-transcode-hurry-up", true));
+
+        // Otherwise a stale output would be taken for the result of a failed transcoding.
+        match fs::remove_file(&self.output) {
+            Err(ref e) if e.kind() != io::ErrorKind::NotFound => return Err(TranscodeError::Start),
+            _ => {},
+        }
+
+        let player = MediaPlayer::new(&self.instance).ok_or(TranscodeError::Start)?;
+        player.set_media(&media);
+
+        let (tx, rx) = mpsc::channel();
+        let data = Box::into_raw(Box::new(tx));
+        unsafe{
+            let em = sys::libvlc_media_player_event_manager(player.ptr);
+            for (i, &event_type) in PLAYER_EVENTS.iter().enumerate() {
+                if sys::libvlc_event_attach(em, event_type as i32, transcode_cb, data as *mut c_void) != 0 {
+                    for &event_type in &PLAYER_EVENTS[..i] {
+                        sys::libvlc_event_detach(em, event_type as i32, transcode_cb, data as *mut c_void);
+                    }
+                    drop(Box::from_raw(data));
+                    return Err(TranscodeError::Start);
+                }
+            }
+        }
+
+        let mut handle = TranscodeHandle {
+            player,
+            rx,
+            data,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            output: self.output.clone(),
+            started: Instant::now(),
+            position: 0.0,
+            result: None,
+        };
+        if handle.player.play().is_err() {
+            handle.result = Some(Err(TranscodeError::Start));
+        }
+        Ok(handle)
+    }
+
+    // A new media for the location of the input, with the options applied to the input.
+    // The options of the transcoding are not added to the input, which would keep them
+    // since a copy made by Media::duplicate() shares the input item.
+    fn input(&self) -> Result<Media, TranscodeError> {
+        if self.media.has_callbacks() {
+            return Err(TranscodeError::UnsupportedMedia);
+        }
+        let mrl = self.media.mrl().ok_or(TranscodeError::Start)?;
+        let media = Media::new_location(&self.instance, &mrl).ok_or(TranscodeError::Start)?;
+        for option in self.media.applied_options() {
+            media.add_option_flag(&option.option, option.trusted, option.unique);
+        }
+        Ok(media)
+    }
+
+    /// Transcode and wait for the end.
+    pub fn run(&self) -> Result<TranscodeOutput, TranscodeError> {
+        self.start()?.wait()
+    }
+}
+
+/// Handle to a running transcoding. Iterating over it yields the progress until the end.
+/// Dropping it cancels the transcoding.
+pub struct TranscodeHandle {
+    player: MediaPlayer,
+    rx: Receiver<Event>,
+    // Sender of transcode_cb, freed once the callbacks are detached.
+    data: *mut Sender<Event>,
+    cancelled: Arc<AtomicBool>,
+    output: PathBuf,
+    started: Instant,
+    position: f32,
+    result: Option<Result<TranscodeOutput, TranscodeError>>,
+}
+
+// The only raw pointer is the data of the callbacks, a Sender owned by the handle.
+// libvlc event managers may be used from any thread.
+unsafe impl Send for TranscodeHandle {}
+
+impl TranscodeHandle {
+    /// Returns a canceller which can be used from another thread.
+    pub fn canceller(&self) -> TranscodeCanceller {
+        TranscodeCanceller{ cancelled: self.cancelled.clone() }
+    }
+
+    /// Cancel the transcoding.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait for the end, and return the result.
+    pub fn wait(mut self) -> Result<TranscodeOutput, TranscodeError> {
+        while self.next().is_some() {}
+        self.result.take().unwrap()
+    }
+
+    fn progress(&self) -> TranscodeProgress {
+        TranscodeProgress{ position: self.position, time: self.player.get_time() }
+    }
+
+    fn finish(&mut self, result: Result<(), TranscodeError>) {
+        // Stopping closes the output, so the muxer writes its trailer.
+        self.player.stop();
+        self.result = Some(result.and_then(|()| match fs::metadata(&self.output) {
+            Ok(metadata) if metadata.len() > 0 => Ok(TranscodeOutput {
+                path: self.output.clone(),
+                size: metadata.len(),
+                elapsed: self.started.elapsed(),
+            }),
+            _ => Err(TranscodeError::NoOutput),
+        }));
+    }
+}
+
+impl Iterator for TranscodeHandle {
+    type Item = TranscodeProgress;
+
+    fn next(&mut self) -> Option<TranscodeProgress> {
+        while self.result.is_none() {
+            if self.cancelled.load(Ordering::SeqCst) {
+                self.finish(Err(TranscodeError::Cancelled));
+                break;
+            }
+            match self.rx.recv_timeout(POLL_INTERVAL) {
+                Ok(Event::MediaPlayerPositionChanged(position)) => {
+                    self.position = position;
+                    return Some(self.progress());
+                },
+                Ok(Event::MediaPlayerTimeChanged) => return Some(self.progress()),
+                Ok(Event::MediaPlayerEndReached) => {
+                    self.position = 1.0;
+                    self.finish(Ok(()));
+                },
+                Ok(Event::MediaPlayerEncounteredError) => {
+                    let msg = crate::errmsg();
+                    crate::clearerr();
+                    self.finish(Err(TranscodeError::Failed(msg)));
+                },
+                // Stopped before the end, by someone else.
+                Ok(Event::MediaPlayerStopped) | Err(RecvTimeoutError::Disconnected) =>
+                    self.finish(Err(TranscodeError::Failed(None))),
+                Ok(_) | Err(RecvTimeoutError::Timeout) => {},
+            }
+        }
+        None
+    }
+}
+
+impl Drop for TranscodeHandle {
+    fn drop(&mut self) {
+        if self.result.is_none() {
+            self.player.stop();
+        }
+        unsafe{
+            let em = sys::libvlc_media_player_event_manager(self.player.ptr);
+            for &event_type in PLAYER_EVENTS {
+                sys::libvlc_event_detach(em, event_type as i32, transcode_cb, self.data as *mut c_void);
+            }
+            drop(Box::from_raw(self.data));
+        }
+    }
+}
+
+unsafe extern "C" fn transcode_cb(pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    let tx = &*(data as *const Sender<Event>);
+    let _ = tx.send(conv_event(pe));
+}
+
+/// Cancels a transcoding, see TranscodeHandle::canceller().
+#[derive(Clone)]
+pub struct TranscodeCanceller {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TranscodeCanceller {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
@@ -1,147 +1,693 @@
+use std::error;
 use std::ffi::CString;
-use std::os::raw::c_char;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
 use std::ptr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use libc::c_void;
 
 use crate::{Instance, sys};
-use crate::tools::{from_cstr, to_cstr};
+use crate::core::{Event, conv_event};
+use crate::enums::EventType;
+use crate::tools::from_cstr;
+
+/// Error of a VLM operation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum VlmError {
+    /// An argument is invalid, e.g. a string contains a nul byte.
+    InvalidArgument(&'static str),
+    /// libvlc failed, with its error message if any.
+    Failed(Option<String>),
+    /// No media has this name.
+    NotFound(String),
+    /// The output of libvlc could not be parsed.
+    Parse(String),
+    /// The operation is not implemented by this Vlm implementor.
+    Unsupported,
+}
+
+impl fmt::Display for VlmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VlmError::InvalidArgument(arg) => write!(f, "invalid VLM argument: {}", arg),
+            VlmError::Failed(Some(ref msg)) => write!(f, "VLM operation failed: {}", msg),
+            VlmError::Failed(None) => write!(f, "VLM operation failed"),
+            VlmError::NotFound(ref name) => write!(f, "no VLM media named {}", name),
+            VlmError::Parse(ref msg) => write!(f, "invalid VLM output: {}", msg),
+            VlmError::Unsupported => write!(f, "VLM operation not supported"),
+        }
+    }
+}
+
+impl error::Error for VlmError {}
 
+/// The VLM (video LAN manager) API of an instance.
+///
+/// The methods with a default implementation return VlmError::Unsupported
+/// (or build on show_media()), so that implementors written for the first methods keep compiling.
 pub trait Vlm {
-    fn add_broadcast(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool, ) -> Result<(), ()>;
+    fn add_broadcast(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool, ) -> Result<(), VlmError>;
+
+    fn add_vod(&self, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<(), VlmError>;
+
+    fn play_media(&self, name: &str) -> Result<(), VlmError>;
+
+    fn pause_media(&self, name: &str) -> Result<(), VlmError>;
+
+    fn stop_media(&self, name: &str) -> Result<(), VlmError>;
+
+    fn get_media_instance_position(&self, name: &str, instance: i32) -> Result<f32, VlmError>;
+
+    fn get_media_instance_length(&self, name: &str, instance: i32) -> Result<i32, VlmError>;
+
+    fn get_media_instance_time(&self, name: &str, instance: i32) -> Result<i32, VlmError>;
+
+    fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, VlmError>;
+
+    /// Returns the description of the named media, in the format of libvlc_vlm_show_media().
+    /// Fails with VlmError::NotFound if there is no such media.
+    fn show_media(&self, name: &str) -> Result<String, VlmError>;
+
+    /// Get the description of the named media.
+    /// With an empty name, the media listed by media_names() are described.
+    fn show_media_info(&self, name: &str) -> Result<Vec<VlmMediaInfo>, VlmError> {
+        if !name.is_empty() {
+            return Ok(vec![VlmMediaInfo::parse(&self.show_media(name)?)?]);
+        }
+        let mut media = Vec::new();
+        for name in self.media_names()? {
+            match self.show_media(&name) {
+                Ok(show) => media.push(VlmMediaInfo::parse(&show)?),
+                // Deleted by other means in the meantime.
+                Err(VlmError::NotFound(_)) => {},
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(media)
+    }
 
-    fn add_vod(&self, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<(), ()>;
+    /// Returns the names of the media.
+    /// libvlc does not list them, so an Instance follows the media added and removed from
+    /// its first call to add_broadcast(), add_vod() or media_names(), whatever adds them
+    /// (this crate, raw libvlc calls or the VLM telnet interface).
+    /// Media added before, such as those of a --vlm-conf file, are not listed.
+    fn media_names(&self) -> Result<Vec<String>, VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn play_media(&self, name: &str) -> Result<(), ()>;
+    /// Delete a media (VOD or broadcast).
+    fn del_media(&self, _name: &str) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn pause_media(&self, name: &str) -> Result<(), ()>;
+    /// Enable or disable a media (VOD or broadcast).
+    fn set_enabled(&self, _name: &str, _enabled: bool) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn stop_media(&self, name: &str) -> Result<(), ()>;
+    /// Set the output for a media.
+    fn set_output(&self, _name: &str, _output: &str) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn get_media_instance_position(&self, name: &str, instance: i32) -> Result<f32, ()>;
+    /// Set a media's input MRL. This will delete all existing inputs and add the specified one.
+    fn set_input(&self, _name: &str, _input: &str) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn get_media_instance_length(&self, name: &str, instance: i32) -> Result<i32, ()>;
+    /// Add a media's input MRL. This will add the specified one.
+    fn add_input(&self, _name: &str, _input: &str) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn get_media_instance_time(&self, name: &str, instance: i32) -> Result<i32, ()>;
+    /// Set a media's loop status.
+    fn set_loop(&self, _name: &str, _loop_broadcast: bool) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, ()>;
+    /// Set a media's VOD muxer.
+    fn set_mux(&self, _name: &str, _mux: &str) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
+
+    /// Edit the parameters of a media. This will delete all existing inputs and add the specified one.
+    fn change_media(&self, _name: &str, _input: &str, _output: &str, _options: Option<Vec<String>>, _enabled: bool, _loop_broadcast: bool) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
+
+    /// Seek in the named broadcast, `percentage` is between 0 and 100.
+    fn seek_media(&self, _name: &str, _percentage: f32) -> Result<(), VlmError> {
+        Err(VlmError::Unsupported)
+    }
 
-    fn show_media(&self, name: &str) -> Result<String, ()>;
+    /// Release the VLM instance related resources. All the media are deleted.
+    fn vlm_release(&self);
 }
 
-impl Vlm for Instance {
-    fn add_broadcast(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool, ) -> Result<(), ()> {
-        let name = to_cstr(name);
-        let input = to_cstr(input);
-        let output = to_cstr(output);
-        let opts_c_ptr: Vec<*const c_char>;
-        let opts_c: Vec<CString>;
-        let enabled = if enabled { 1 } else { 0 };
-        let loop_broadcast = if loop_broadcast { 1 } else { 0 };
-        if let Some(vec) = options {
-            opts_c = vec.into_iter()
-                .map(|x| CString::new(x).expect("Error: Unexpected null byte")).collect();
-            opts_c_ptr = opts_c.iter().map(|x| x.as_ptr()).collect();
-        } else {
-            opts_c_ptr = Vec::new();
+// Converts a string argument, which must not contain nul bytes.
+fn cstr_arg(s: &str, arg: &'static str) -> Result<CString, VlmError> {
+    CString::new(s).map_err(|_| VlmError::InvalidArgument(arg))
+}
+
+// Calls libvlc_vlm_show_media and frees the returned string.
+fn show_media_raw(instance: &Instance, name: &str) -> Result<String, VlmError> {
+    let cname = cstr_arg(name, "name")?;
+    unsafe {
+        crate::clearerr();
+        let p = sys::libvlc_vlm_show_media(instance.ptr, cname.as_ptr());
+        if p.is_null() {
+            // libvlc sets no error message when the show command finds nothing.
+            return Err(match crate::errmsg() {
+                Some(_) => vlm_failed(),
+                None => VlmError::NotFound(name.to_owned()),
+            });
         }
-        let result = unsafe {
-            if opts_c_ptr.is_empty() {
-                sys::libvlc_vlm_add_broadcast(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), 0, ptr::null(), enabled, loop_broadcast)
-            } else {
-                sys::libvlc_vlm_add_broadcast(self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(), opts_c_ptr.len() as i32, opts_c_ptr.as_ptr(), enabled, loop_broadcast)
+        let data = from_cstr(p);
+        sys::libvlc_free(p as *mut c_void);
+        Ok(data.unwrap_or_default())
+    }
+}
+
+const VLM_NAME_EVENTS: &[EventType] = &[EventType::VlmMediaAdded, EventType::VlmMediaRemoved];
+
+// Names of the VLM media of an instance, see Vlm::media_names().
+// They are followed from the VLM events once the VLM is used through Vlm.
+// It holds its own reference to the libvlc instance, so that the callback
+// is detached before the instance is released.
+pub(crate) struct VlmNames {
+    instance: *mut sys::libvlc_instance_t,
+    names: Mutex<Vec<String>>,
+    // Whether vlm_names_cb is attached to the VLM event manager.
+    attached: Mutex<bool>,
+}
+
+unsafe impl Send for VlmNames {}
+unsafe impl Sync for VlmNames {}
+
+impl VlmNames {
+    pub(crate) fn new(instance: *mut sys::libvlc_instance_t) -> VlmNames {
+        unsafe{ sys::libvlc_retain(instance) };
+        VlmNames{ instance, names: Mutex::new(Vec::new()), attached: Mutex::new(false) }
+    }
+
+    // Follow the media added and removed from now on. This creates the VLM of the instance.
+    fn attach(&self) {
+        let mut attached = self.attached.lock().unwrap();
+        if *attached {
+            return;
+        }
+        unsafe{
+            let em = sys::libvlc_vlm_get_event_manager(self.instance);
+            if em.is_null() {
+                return;
+            }
+            let data = self as *const VlmNames as *mut c_void;
+            for (i, &event_type) in VLM_NAME_EVENTS.iter().enumerate() {
+                if sys::libvlc_event_attach(em, event_type as i32, vlm_names_cb, data) != 0 {
+                    for &event_type in &VLM_NAME_EVENTS[..i] {
+                        sys::libvlc_event_detach(em, event_type as i32, vlm_names_cb, data);
+                    }
+                    return;
+                }
             }
-        };
-        if result == 0 { Ok(()) } else { Err(()) }
-    }
-
-    fn add_vod(&self, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<(), ()> {
-        let name = to_cstr(name);
-        let input = to_cstr(input);
-        let mux = to_cstr(mux);
-        let opts_c_ptr: Vec<*const c_char>;
-        let opts_c: Vec<CString>;
-        let enabled = if enabled { 1 } else { 0 };
-        if let Some(vec) = options {
-            opts_c = vec.into_iter()
-                .map(|x| CString::new(x).expect("Error: Unexpected null byte")).collect();
-            opts_c_ptr = opts_c.iter().map(|x| x.as_ptr()).collect();
-        } else {
-            opts_c_ptr = Vec::new();
         }
-        let result = unsafe {
-            if opts_c_ptr.is_empty() {
-                sys::libvlc_vlm_add_vod(self.ptr, name.as_ptr(), input.as_ptr(), 0, ptr::null(), enabled, mux.as_ptr())
-            } else {
-                sys::libvlc_vlm_add_vod(self.ptr, name.as_ptr(), input.as_ptr(), opts_c_ptr.len() as i32, opts_c_ptr.as_ptr(), enabled, mux.as_ptr())
+        *attached = true;
+    }
+
+    fn detach(&self) {
+        let mut attached = self.attached.lock().unwrap();
+        if !*attached {
+            return;
+        }
+        unsafe{
+            let em = sys::libvlc_vlm_get_event_manager(self.instance);
+            for &event_type in VLM_NAME_EVENTS {
+                sys::libvlc_event_detach(em, event_type as i32, vlm_names_cb, self as *const VlmNames as *mut c_void);
             }
-        };
-        if result == 0 { Ok(()) } else { Err(()) }
+        }
+        *attached = false;
     }
 
-    fn play_media(&self, name: &str) -> Result<(), ()> {
-        let name = to_cstr(name);
-        let result = unsafe {
+    fn add(&self, name: &str) {
+        let mut names = self.names.lock().unwrap();
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_owned());
+        }
+    }
+
+    fn remove(&self, name: &str) {
+        self.names.lock().unwrap().retain(|n| n != name);
+    }
+}
+
+impl Drop for VlmNames {
+    fn drop(&mut self) {
+        self.detach();
+        unsafe{ sys::libvlc_release(self.instance) };
+    }
+}
+
+unsafe extern "C" fn vlm_names_cb(pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    let names = &*(data as *const VlmNames);
+    let event = conv_event(pe);
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| match event {
+        Event::VlmMediaAdded(_, Some(name)) => names.add(&name),
+        Event::VlmMediaRemoved(_, Some(name)) => names.remove(&name),
+        _ => (),
+    }));
+}
+
+// Converts a libvlc return code, taking the libvlc error message on failure.
+fn vlm_result(result: c_int) -> Result<(), VlmError> {
+    if result == 0 { Ok(()) } else { Err(vlm_failed()) }
+}
+
+// Takes the libvlc error message after a failed VLM call.
+pub(crate) fn vlm_failed() -> VlmError {
+    let msg = crate::errmsg();
+    crate::clearerr();
+    VlmError::Failed(msg)
+}
+
+// Converts the options of a media.
+fn cstr_options(options: Option<Vec<String>>) -> Result<Vec<CString>, VlmError> {
+    options.unwrap_or_default().iter().map(|x| cstr_arg(x, "options")).collect()
+}
+
+impl Vlm for Instance {
+    fn add_broadcast(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool, ) -> Result<(), VlmError> {
+        let cname = cstr_arg(name, "name")?;
+        let input = cstr_arg(input, "input")?;
+        let output = cstr_arg(output, "output")?;
+        let opts_c = cstr_options(options)?;
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
+        self.vlm_names.attach();
+        vlm_result(unsafe {
+            sys::libvlc_vlm_add_broadcast(
+                self.ptr, cname.as_ptr(), input.as_ptr(), output.as_ptr(),
+                opts_c_ptr.len() as c_int, if opts_c_ptr.is_empty() { ptr::null() } else { opts_c_ptr.as_ptr() },
+                enabled as c_int, loop_broadcast as c_int)
+        })?;
+        self.vlm_names.add(name);
+        Ok(())
+    }
+
+    fn add_vod(&self, name: &str, input: &str, mux: &str, options: Option<Vec<String>>, enabled: bool) -> Result<(), VlmError> {
+        let cname = cstr_arg(name, "name")?;
+        let input = cstr_arg(input, "input")?;
+        let mux = cstr_arg(mux, "mux")?;
+        let opts_c = cstr_options(options)?;
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
+        self.vlm_names.attach();
+        vlm_result(unsafe {
+            sys::libvlc_vlm_add_vod(
+                self.ptr, cname.as_ptr(), input.as_ptr(),
+                opts_c_ptr.len() as c_int, if opts_c_ptr.is_empty() { ptr::null() } else { opts_c_ptr.as_ptr() },
+                enabled as c_int, mux.as_ptr())
+        })?;
+        self.vlm_names.add(name);
+        Ok(())
+    }
+
+    fn play_media(&self, name: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
             sys::libvlc_vlm_play_media(self.ptr, name.as_ptr())
-        };
-        if result == 0 { Ok(()) } else { Err(()) }
+        })
     }
 
-    fn pause_media(&self, name: &str) -> Result<(), ()> {
-        let name = to_cstr(name);
-        let result = unsafe {
+    fn pause_media(&self, name: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
             sys::libvlc_vlm_pause_media(self.ptr, name.as_ptr())
-        };
-        if result == 0 { Ok(()) } else { Err(()) }
+        })
     }
 
-    fn stop_media(&self, name: &str) -> Result<(), ()> {
-        let name = to_cstr(name);
-        let result = unsafe {
+    fn stop_media(&self, name: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
             sys::libvlc_vlm_stop_media(self.ptr, name.as_ptr())
-        };
-        if result == 0 { Ok(()) } else { Err(()) }
+        })
     }
 
-    fn get_media_instance_position(&self, name: &str, instance: i32) -> Result<f32, ()> {
-        let name = to_cstr(name);
+    fn get_media_instance_position(&self, name: &str, instance: i32) -> Result<f32, VlmError> {
+        let name = cstr_arg(name, "name")?;
         let result = unsafe {
             sys::libvlc_vlm_get_media_instance_position(self.ptr, name.as_ptr(), instance)
         };
-        if result != -1f32 { Ok(result) } else { Err(()) }
+        if result != -1f32 { Ok(result) } else { Err(vlm_failed()) }
     }
 
-    fn get_media_instance_length(&self, name: &str, instance: i32) -> Result<i32, ()> {
-        let name = to_cstr(name);
+    fn get_media_instance_length(&self, name: &str, instance: i32) -> Result<i32, VlmError> {
+        let name = cstr_arg(name, "name")?;
         let result = unsafe {
             sys::libvlc_vlm_get_media_instance_length(self.ptr, name.as_ptr(), instance)
         };
-        if result != -1 { Ok(result) } else { Err(()) }
+        if result != -1 { Ok(result) } else { Err(vlm_failed()) }
     }
 
-    fn get_media_instance_time(&self, name: &str, instance: i32) -> Result<i32, ()> {
-        let name = to_cstr(name);
+    fn get_media_instance_time(&self, name: &str, instance: i32) -> Result<i32, VlmError> {
+        let name = cstr_arg(name, "name")?;
         let result = unsafe {
             sys::libvlc_vlm_get_media_instance_time(self.ptr, name.as_ptr(), instance)
         };
-        if result != -1 { Ok(result) } else { Err(()) }
+        if result != -1 { Ok(result) } else { Err(vlm_failed()) }
     }
 
-    fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, ()> {
-        let name = to_cstr(name);
+    fn get_media_instance_rate(&self, name: &str, instance: i32) -> Result<i32, VlmError> {
+        let name = cstr_arg(name, "name")?;
         let result = unsafe {
             sys::libvlc_vlm_get_media_instance_rate(self.ptr, name.as_ptr(), instance)
         };
-        if result != -1 { Ok(result) } else { Err(()) }
+        if result != -1 { Ok(result) } else { Err(vlm_failed()) }
     }
 
-    fn show_media(&self, name: &str) -> Result<String, ()> {
-        let name = to_cstr(name);
-        let result = unsafe {
-            from_cstr(sys::libvlc_vlm_show_media(self.ptr, name.as_ptr()))
+    fn show_media(&self, name: &str) -> Result<String, VlmError> {
+        show_media_raw(self, name)
+    }
+
+    fn media_names(&self) -> Result<Vec<String>, VlmError> {
+        self.vlm_names.attach();
+        Ok(self.vlm_names.names.lock().unwrap().clone())
+    }
+
+    fn del_media(&self, name: &str) -> Result<(), VlmError> {
+        let cname = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_del_media(self.ptr, cname.as_ptr())
+        })?;
+        self.vlm_names.remove(name);
+        Ok(())
+    }
+
+    fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_set_enabled(self.ptr, name.as_ptr(), enabled as c_int)
+        })
+    }
+
+    fn set_output(&self, name: &str, output: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        let output = cstr_arg(output, "output")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_set_output(self.ptr, name.as_ptr(), output.as_ptr())
+        })
+    }
+
+    fn set_input(&self, name: &str, input: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        let input = cstr_arg(input, "input")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_set_input(self.ptr, name.as_ptr(), input.as_ptr())
+        })
+    }
+
+    fn add_input(&self, name: &str, input: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        let input = cstr_arg(input, "input")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_add_input(self.ptr, name.as_ptr(), input.as_ptr())
+        })
+    }
+
+    fn set_loop(&self, name: &str, loop_broadcast: bool) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_set_loop(self.ptr, name.as_ptr(), loop_broadcast as c_int)
+        })
+    }
+
+    fn set_mux(&self, name: &str, mux: &str) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        let mux = cstr_arg(mux, "mux")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_set_mux(self.ptr, name.as_ptr(), mux.as_ptr())
+        })
+    }
+
+    fn change_media(&self, name: &str, input: &str, output: &str, options: Option<Vec<String>>, enabled: bool, loop_broadcast: bool) -> Result<(), VlmError> {
+        let name = cstr_arg(name, "name")?;
+        let input = cstr_arg(input, "input")?;
+        let output = cstr_arg(output, "output")?;
+        let opts_c = cstr_options(options)?;
+        let opts_c_ptr: Vec<*const c_char> = opts_c.iter().map(|x| x.as_ptr()).collect();
+        vlm_result(unsafe {
+            sys::libvlc_vlm_change_media(
+                self.ptr, name.as_ptr(), input.as_ptr(), output.as_ptr(),
+                opts_c_ptr.len() as c_int, if opts_c_ptr.is_empty() { ptr::null() } else { opts_c_ptr.as_ptr() },
+                enabled as c_int, loop_broadcast as c_int)
+        })
+    }
+
+    fn seek_media(&self, name: &str, percentage: f32) -> Result<(), VlmError> {
+        if !(0.0..=100.0).contains(&percentage) {
+            return Err(VlmError::InvalidArgument("percentage"));
+        }
+        let name = cstr_arg(name, "name")?;
+        vlm_result(unsafe {
+            sys::libvlc_vlm_seek_media(self.ptr, name.as_ptr(), percentage)
+        })
+    }
+
+    fn vlm_release(&self) {
+        // The event manager is released with the VLM.
+        self.vlm_names.detach();
+        unsafe { sys::libvlc_vlm_release(self.ptr) };
+        self.vlm_names.names.lock().unwrap().clear();
+    }
+}
+
+/// Type of a VLM media.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VlmMediaType {
+    Broadcast,
+    Vod,
+}
+
+/// Status of a VLM media instance, as reported by the VlmMediaInstanceStatus* events.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VlmInstanceStatus {
+    Init,
+    Opening,
+    Playing,
+    Pause,
+    End,
+    Error,
+}
+
+/// Description of a VLM media, see Vlm::show_media_info().
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VlmMediaInfo {
+    pub name: String,
+    pub media_type: VlmMediaType,
+    pub enabled: bool,
+    /// Always false for a VOD.
+    pub loop_broadcast: bool,
+    /// Muxer of a VOD.
+    pub mux: Option<String>,
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    /// The options, whose text is None when libvlc does not report it, as libvlc 3.
+    pub options: Vec<Option<String>>,
+    pub instances: Vec<VlmInstanceInfo>,
+}
+
+/// Description of a running instance of a VLM media.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VlmInstanceInfo {
+    pub name: String,
+    /// Playing or Pause, or None when libvlc reports the instance as stopped (e.g. while opening).
+    pub status: Option<VlmInstanceStatus>,
+    pub position: f32,
+    /// Time (in ms).
+    pub time: i64,
+    /// Length (in ms).
+    pub length: i64,
+    pub rate: f32,
+}
+
+impl VlmMediaInfo {
+    /// Parse the output of Vlm::show_media() for a media.
+    pub fn parse(show: &str) -> Result<VlmMediaInfo, VlmError> {
+        let fields = match (ShowParser{ s: show, pos: 0 }).node() {
+            Some(ShowNode::Object(fields)) => fields,
+            _ => return Err(VlmError::Parse("expected a media object".to_owned())),
+        };
+        match fields.as_slice() {
+            // The media written as a named child, rather than with a "name" field.
+            [(name, ShowNode::Object(fields))] => VlmMediaInfo::from_fields(Some(name), fields),
+            _ => VlmMediaInfo::from_fields(None, &fields),
+        }
+    }
+
+    fn from_fields(name: Option<&str>, fields: &[(String, ShowNode)]) -> Result<VlmMediaInfo, VlmError> {
+        let media_type = match value(fields, "type") {
+            Some("broadcast") => VlmMediaType::Broadcast,
+            Some("vod") => VlmMediaType::Vod,
+            _ => return Err(VlmError::Parse("unknown media type".to_owned())),
+        };
+        let instances = match field(fields, "instances") {
+            Some(ShowNode::Object(instances)) => instances.iter()
+                .filter_map(|(key, node)| match node {
+                    ShowNode::Object(fields) if key == "instance" => Some(VlmInstanceInfo::from_fields(fields)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
         };
-        if let Some(data) = result {
-            Ok(data.to_string())
-        } else {
-            Err(())
+        // The object of a media starts with its name.
+        let name = name.or_else(|| value(fields, "name"))
+            .ok_or_else(|| VlmError::Parse("missing media name".to_owned()))?;
+
+        Ok(VlmMediaInfo {
+            name: name.to_owned(),
+            media_type,
+            enabled: value(fields, "enabled") == Some("yes"),
+            loop_broadcast: value(fields, "loop") == Some("yes"),
+            mux: non_empty(value(fields, "mux")),
+            inputs: list(fields, "inputs").into_iter().flatten().collect(),
+            output: non_empty(value(fields, "output")),
+            options: list(fields, "options"),
+            instances,
+        })
+    }
+}
+
+impl VlmInstanceInfo {
+    fn from_fields(fields: &[(String, ShowNode)]) -> VlmInstanceInfo {
+        let number = |key| value(fields, key).and_then(|v| v.parse::<f64>().ok());
+        // The object starts with the "instance" tag, followed by the name of the instance.
+        let name = fields.iter().rev().find(|(k, _)| k == "name").and_then(|(_, node)| match node {
+            ShowNode::Value(v) => Some(v.as_str()),
+            _ => None,
+        });
+        VlmInstanceInfo {
+            name: name.unwrap_or("default").to_owned(),
+            status: match value(fields, "state") {
+                Some("playing") => Some(VlmInstanceStatus::Playing),
+                Some("paused") => Some(VlmInstanceStatus::Pause),
+                _ => None,
+            },
+            position: number("position").unwrap_or(0.0) as f32,
+            // libvlc reports times in us.
+            time: number("time").map_or(0, |us| (us / 1000.0) as i64),
+            length: number("length").map_or(0, |us| (us / 1000.0) as i64),
+            rate: number("rate").unwrap_or(1.0) as f32,
+        }
+    }
+}
+
+// Node of the show_media output. It looks like JSON, but keys may be repeated,
+// strings are not escaped and a node without value nor children is null.
+enum ShowNode {
+    Null,
+    Value(String),
+    Object(Vec<(String, ShowNode)>),
+    List(Vec<ShowNode>),
+}
+
+fn field<'a>(fields: &'a [(String, ShowNode)], key: &str) -> Option<&'a ShowNode> {
+    fields.iter().find(|(k, _)| k == key).map(|(_, node)| node)
+}
+
+fn value<'a>(fields: &'a [(String, ShowNode)], key: &str) -> Option<&'a str> {
+    match field(fields, key) {
+        Some(ShowNode::Value(v)) => Some(v.as_str()),
+        _ => None,
+    }
+}
+
+// Returns the items of a list, which is null when empty.
+fn list(fields: &[(String, ShowNode)], key: &str) -> Vec<Option<String>> {
+    match field(fields, key) {
+        Some(ShowNode::List(items)) => items.iter().map(|item| match *item {
+            ShowNode::Value(ref v) => Some(v.clone()),
+            _ => None,
+        }).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn non_empty(v: Option<&str>) -> Option<String> {
+    v.filter(|v| !v.is_empty()).map(|v| v.to_owned())
+}
+
+struct ShowParser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> ShowParser<'a> {
+    // Skips whitespaces and separators, and returns the next char.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.s[self.pos..];
+        let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        self.pos += rest.len() - trimmed.len();
+        trimmed.chars().next()
+    }
+
+    fn node(&mut self) -> Option<ShowNode> {
+        match self.peek()? {
+            '"' => self.string().map(ShowNode::Value),
+            'n' if self.s[self.pos..].starts_with("null") => {
+                self.pos += 4;
+                Some(ShowNode::Null)
+            },
+            '{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                loop {
+                    match self.peek()? {
+                        '}' => { self.pos += 1; break; },
+                        '"' => {
+                            let key = self.string()?;
+                            if self.peek()? != ':' {
+                                return None;
+                            }
+                            self.pos += 1;
+                            fields.push((key, self.node()?));
+                        },
+                        _ => return None,
+                    }
+                }
+                Some(ShowNode::Object(fields))
+            },
+            '[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    if self.peek()? == ']' {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.node()?);
+                }
+                Some(ShowNode::List(items))
+            },
+            _ => None,
+        }
+    }
+
+    // As strings are not escaped, a string ends at the first quote followed by a separator.
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos + 1;
+        let mut end = start;
+        loop {
+            end += self.s[end..].find('"')?;
+            let after = self.s[end + 1..].trim_start_matches([' ', '\t']);
+            if after.is_empty() || after.starts_with(|c| ",:}]\n\r".contains(c)) {
+                break;
+            }
+            end += 1;
         }
+        self.pos = end + 1;
+        Some(self.s[start..end].to_owned())
     }
 }
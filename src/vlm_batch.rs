@@ -0,0 +1,352 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::Instance;
+use crate::vlm::{Vlm, VlmError, VlmMediaInfo, VlmMediaType, VlmInstanceStatus};
+use crate::vlm_config::{BroadcastConfig, VodConfig};
+
+/// A control command of a VLM batch file.
+#[derive(Clone, PartialEq, Debug)]
+pub enum VlmControl {
+    Play(String),
+    Pause(String),
+    Stop(String),
+    /// Seek to a position between 0 and 100.
+    Seek(String, f32),
+    Delete(String),
+}
+
+/// A media or a command of a VLM batch file.
+#[derive(Clone, PartialEq, Debug)]
+pub enum VlmBatchItem {
+    Broadcast(BroadcastConfig),
+    Vod(VodConfig),
+    Control(VlmControl),
+}
+
+impl VlmBatchItem {
+    fn media_name(&self) -> Option<&str> {
+        match *self {
+            VlmBatchItem::Broadcast(ref config) => Some(config.name()),
+            VlmBatchItem::Vod(ref config) => Some(config.name()),
+            VlmBatchItem::Control(_) => None,
+        }
+    }
+}
+
+/// A VLM batch file, as loaded by VLC with --vlm-conf.
+///
+/// The `new`, `setup`, `control` and `del` commands are supported. Schedules are not.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct VlmBatch {
+    items: Vec<VlmBatchItem>,
+}
+
+impl VlmBatch {
+    pub fn new() -> VlmBatch {
+        VlmBatch::default()
+    }
+
+    /// Returns the media and commands in file order.
+    pub fn items(&self) -> &[VlmBatchItem] {
+        &self.items
+    }
+
+    pub fn push(&mut self, item: VlmBatchItem) {
+        self.items.push(item);
+    }
+
+    /// Parse the text of a batch file.
+    pub fn parse(text: &str) -> Result<VlmBatch, VlmError> {
+        let mut batch = VlmBatch::new();
+        for (i, line) in text.lines().enumerate() {
+            batch.parse_line(line).map_err(|msg| VlmError::Parse(format!("line {}: {}", i + 1, msg)))?;
+        }
+        Ok(batch)
+    }
+
+    /// Read a batch file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<VlmBatch> {
+        let text = fs::read_to_string(path)?;
+        VlmBatch::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Write the batch file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        // Comments are free text, which may contain unbalanced quotes.
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let words = split_words(line)?;
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+
+        match words.as_slice() {
+            [] => Ok(()),
+            ["new", name, kind, properties @ ..] => {
+                if self.media_index(name).is_some() {
+                    return Err(format!("{} already exists", name));
+                }
+                let item = match *kind {
+                    "broadcast" => VlmBatchItem::Broadcast(BroadcastConfig::new(name).enabled(false)),
+                    "vod" => VlmBatchItem::Vod(VodConfig::new(name).enabled(false)),
+                    "schedule" => return Err("schedules are not supported".to_owned()),
+                    _ => return Err(format!("unknown media type {}", kind)),
+                };
+                self.items.push(item);
+                let index = self.items.len() - 1;
+                self.setup(index, properties)
+            },
+            ["setup", name, properties @ ..] => {
+                let index = self.media_index(name).ok_or_else(|| format!("unknown media {}", name))?;
+                self.setup(index, properties)
+            },
+            ["control", name, command @ ..] => {
+                let name = name.to_string();
+                let control = match *command {
+                    ["play"] => VlmControl::Play(name),
+                    ["pause"] => VlmControl::Pause(name),
+                    ["stop"] => VlmControl::Stop(name),
+                    ["seek", position] => VlmControl::Seek(name, position.trim_end_matches('%').parse()
+                        .map_err(|_| format!("invalid position {}", position))?),
+                    _ => return Err(format!("unsupported control: {}", command.join(" "))),
+                };
+                self.items.push(VlmBatchItem::Control(control));
+                Ok(())
+            },
+            ["del", name] => {
+                self.items.push(VlmBatchItem::Control(VlmControl::Delete(name.to_string())));
+                Ok(())
+            },
+            [command, ..] => Err(format!("unsupported command {}", command)),
+        }
+    }
+
+    fn media_index(&self, name: &str) -> Option<usize> {
+        self.items.iter().rposition(|item| item.media_name() == Some(name))
+    }
+
+    // Applies the properties of a new or setup command.
+    fn setup(&mut self, index: usize, properties: &[&str]) -> Result<(), String> {
+        let mut properties = properties.iter();
+        while let Some(&property) = properties.next() {
+            let mut value = || properties.next().copied().ok_or_else(|| format!("{} needs a value", property));
+            match self.items[index] {
+                VlmBatchItem::Broadcast(ref mut config) => {
+                    let c = config.clone();
+                    *config = match property {
+                        "enabled" => c.enabled(true),
+                        "disabled" => c.enabled(false),
+                        "loop" => c.loop_broadcast(true),
+                        "unloop" => c.loop_broadcast(false),
+                        "input" => c.add_input(value()?),
+                        "output" => c.output(value()?),
+                        "option" => c.option(value()?),
+                        _ => return Err(format!("unsupported broadcast property {}", property)),
+                    };
+                },
+                VlmBatchItem::Vod(ref mut config) => {
+                    let c = config.clone();
+                    *config = match property {
+                        "enabled" => c.enabled(true),
+                        "disabled" => c.enabled(false),
+                        "input" => c.add_input(value()?),
+                        "mux" => c.mux(value()?),
+                        "option" => c.option(value()?),
+                        _ => return Err(format!("unsupported vod property {}", property)),
+                    };
+                },
+                VlmBatchItem::Control(_) => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Create or update the media, then run the commands, in file order.
+    pub fn apply(&self, instance: &Instance) -> Result<(), VlmError> {
+        for item in &self.items {
+            match *item {
+                VlmBatchItem::Broadcast(ref config) => config.apply(instance)?,
+                VlmBatchItem::Vod(ref config) => config.apply(instance)?,
+                VlmBatchItem::Control(ref control) => run_control(instance, control)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a batch from media descriptions. Playing broadcasts get a play command.
+    /// The options whose text is not reported are left out.
+    pub fn from_info(media: &[VlmMediaInfo]) -> VlmBatch {
+        let mut batch = VlmBatch::new();
+        for info in media {
+            match info.media_type {
+                VlmMediaType::Broadcast => {
+                    let mut config = BroadcastConfig::new(&info.name)
+                        .enabled(info.enabled)
+                        .loop_broadcast(info.loop_broadcast);
+                    for input in &info.inputs {
+                        config = config.add_input(input);
+                    }
+                    if let Some(ref output) = info.output {
+                        config = config.output(output);
+                    }
+                    for option in info.options.iter().flatten() {
+                        config = config.option(option);
+                    }
+                    batch.push(VlmBatchItem::Broadcast(config));
+                },
+                VlmMediaType::Vod => {
+                    let mut config = VodConfig::new(&info.name).enabled(info.enabled);
+                    for input in &info.inputs {
+                        config = config.add_input(input);
+                    }
+                    if let Some(ref mux) = info.mux {
+                        config = config.mux(mux);
+                    }
+                    for option in info.options.iter().flatten() {
+                        config = config.option(option);
+                    }
+                    batch.push(VlmBatchItem::Vod(config));
+                },
+            }
+        }
+        for info in media {
+            if info.instances.iter().any(|i| i.status == Some(VlmInstanceStatus::Playing)) {
+                batch.push(VlmBatchItem::Control(VlmControl::Play(info.name.clone())));
+            }
+        }
+        batch
+    }
+
+    /// Export the named media of the instance, with Vlm::show_media_info().
+    /// Without names, the media of Vlm::media_names() are exported.
+    pub fn export(instance: &Instance, names: &[&str]) -> Result<VlmBatch, VlmError> {
+        let mut media = Vec::new();
+        if names.is_empty() {
+            media = instance.show_media_info("")?;
+        }
+        for name in names {
+            media.extend(instance.show_media_info(name)?);
+        }
+        Ok(VlmBatch::from_info(&media))
+    }
+}
+
+fn run_control(instance: &Instance, control: &VlmControl) -> Result<(), VlmError> {
+    match *control {
+        VlmControl::Play(ref name) => instance.play_media(name),
+        VlmControl::Pause(ref name) => instance.pause_media(name),
+        VlmControl::Stop(ref name) => instance.stop_media(name),
+        VlmControl::Seek(ref name, position) => instance.seek_media(name, position),
+        VlmControl::Delete(ref name) => instance.del_media(name),
+    }
+}
+
+impl fmt::Display for VlmBatch {
+    /// Formats the batch in the format of VLC batch files.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for item in &self.items {
+            match *item {
+                VlmBatchItem::Broadcast(ref config) => {
+                    let name = quote(config.name());
+                    writeln!(f, "new {} broadcast {} {}", name,
+                             if config.is_enabled() { "enabled" } else { "disabled" },
+                             if config.is_loop() { "loop" } else { "unloop" })?;
+                    for input in config.inputs() {
+                        writeln!(f, "setup {} input {}", name, quote(input))?;
+                    }
+                    if let Some(output) = config.get_output() {
+                        writeln!(f, "setup {} output {}", name, quote(output))?;
+                    }
+                    for option in config.options() {
+                        writeln!(f, "setup {} option {}", name, quote(option))?;
+                    }
+                },
+                VlmBatchItem::Vod(ref config) => {
+                    let name = quote(config.name());
+                    writeln!(f, "new {} vod {}", name, if config.is_enabled() { "enabled" } else { "disabled" })?;
+                    for input in config.inputs() {
+                        writeln!(f, "setup {} input {}", name, quote(input))?;
+                    }
+                    if let Some(mux) = config.get_mux() {
+                        writeln!(f, "setup {} mux {}", name, quote(mux))?;
+                    }
+                    for option in config.options() {
+                        writeln!(f, "setup {} option {}", name, quote(option))?;
+                    }
+                },
+                VlmBatchItem::Control(ref control) => match *control {
+                    VlmControl::Play(ref name) => writeln!(f, "control {} play", quote(name))?,
+                    VlmControl::Pause(ref name) => writeln!(f, "control {} pause", quote(name))?,
+                    VlmControl::Stop(ref name) => writeln!(f, "control {} stop", quote(name))?,
+                    VlmControl::Seek(ref name, position) => writeln!(f, "control {} seek {}", quote(name), position)?,
+                    VlmControl::Delete(ref name) => writeln!(f, "del {}", quote(name))?,
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+// Splits a line into words. As in VLC, words may be quoted with " or ',
+// and \ escapes a character in double quotes.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut word = String::new();
+        match c {
+            '"' | '\'' => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('\\') if c == '"' => word.push(chars.next().ok_or("unterminated quote")?),
+                        Some(q) if q == c => break,
+                        Some(ch) => word.push(ch),
+                        None => return Err("unterminated quote".to_owned()),
+                    }
+                }
+            },
+            _ => while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            },
+        }
+        words.push(word);
+    }
+    Ok(words)
+}
+
+// Quotes a word if needed.
+fn quote(word: &str) -> String {
+    if !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '\\') {
+        return word.to_owned();
+    }
+    let mut quoted = String::with_capacity(word.len() + 2);
+    quoted.push('"');
+    for c in word.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
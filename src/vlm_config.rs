@@ -0,0 +1,254 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use crate::Instance;
+use crate::vlm::{Vlm, VlmError, VlmMediaInfo, VlmMediaType};
+
+/// Configuration of a VLM broadcast, applied with apply().
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BroadcastConfig {
+    name: String,
+    inputs: Vec<String>,
+    output: Option<String>,
+    options: Vec<String>,
+    enabled: bool,
+    loop_broadcast: bool,
+}
+
+impl BroadcastConfig {
+    /// Create an enabled broadcast without inputs, which does not loop.
+    pub fn new(name: &str) -> BroadcastConfig {
+        BroadcastConfig {
+            name: name.to_owned(),
+            inputs: Vec::new(),
+            output: None,
+            options: Vec::new(),
+            enabled: true,
+            loop_broadcast: false,
+        }
+    }
+
+    /// Add an input MRL. The inputs are played in order.
+    pub fn add_input(mut self, mrl: &str) -> BroadcastConfig {
+        self.inputs.push(mrl.to_owned());
+        self
+    }
+
+    /// Set the output chain, e.g. "#std{access=http,mux=ts,dst=:8080}".
+    pub fn output<S: ToString>(mut self, output: S) -> BroadcastConfig {
+        self.output = Some(output.to_string());
+        self
+    }
+
+    /// Add an option of the inputs, e.g. "sout-keep".
+    pub fn option(mut self, option: &str) -> BroadcastConfig {
+        self.options.push(option.to_owned());
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> BroadcastConfig {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn loop_broadcast(mut self, loop_broadcast: bool) -> BroadcastConfig {
+        self.loop_broadcast = loop_broadcast;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn get_output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_loop(&self) -> bool {
+        self.loop_broadcast
+    }
+
+    /// Check the configuration, without calling libvlc.
+    pub fn validate(&self) -> Result<(), VlmError> {
+        validate_name(&self.name)?;
+        validate_inputs(&self.inputs)?;
+        if self.output.as_ref().is_some_and(|output| output.contains('\0')) {
+            return Err(VlmError::InvalidArgument("output"));
+        }
+        validate_options(&self.options)
+    }
+
+    /// Create the broadcast, or update it if a media of this name already exists.
+    /// The inputs and options of an existing media are replaced, so applying twice is harmless.
+    pub fn apply(&self, instance: &Instance) -> Result<(), VlmError> {
+        self.validate()?;
+
+        let output = self.output.as_deref().unwrap_or("");
+        let options = Some(self.options.clone());
+        match existing_type(instance, &self.name)? {
+            Some(VlmMediaType::Broadcast) => {
+                instance.change_media(&self.name, &self.inputs[0], output, options, self.enabled, self.loop_broadcast)?;
+            },
+            existing => {
+                if existing.is_some() {
+                    instance.del_media(&self.name)?;
+                }
+                instance.add_broadcast(&self.name, &self.inputs[0], output, options, self.enabled, self.loop_broadcast)?;
+            },
+        }
+        add_inputs(instance, &self.name, &self.inputs[1..])
+    }
+}
+
+/// Configuration of a VLM video on demand, applied with apply().
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct VodConfig {
+    name: String,
+    inputs: Vec<String>,
+    mux: Option<String>,
+    options: Vec<String>,
+    enabled: bool,
+}
+
+impl VodConfig {
+    /// Create an enabled VOD without inputs.
+    pub fn new(name: &str) -> VodConfig {
+        VodConfig {
+            name: name.to_owned(),
+            inputs: Vec::new(),
+            mux: None,
+            options: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    /// Add an input MRL.
+    pub fn add_input(mut self, mrl: &str) -> VodConfig {
+        self.inputs.push(mrl.to_owned());
+        self
+    }
+
+    /// Set the muxer, e.g. "mp2t".
+    pub fn mux(mut self, mux: &str) -> VodConfig {
+        self.mux = Some(mux.to_owned());
+        self
+    }
+
+    /// Add an option of the inputs.
+    pub fn option(mut self, option: &str) -> VodConfig {
+        self.options.push(option.to_owned());
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> VodConfig {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn get_mux(&self) -> Option<&str> {
+        self.mux.as_deref()
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Check the configuration, without calling libvlc.
+    pub fn validate(&self) -> Result<(), VlmError> {
+        validate_name(&self.name)?;
+        validate_inputs(&self.inputs)?;
+        if self.mux.as_ref().is_some_and(|mux| mux.is_empty() || mux.contains(char::is_whitespace) || mux.contains('\0')) {
+            return Err(VlmError::InvalidArgument("mux"));
+        }
+        validate_options(&self.options)
+    }
+
+    /// Create the VOD, or update it if a media of this name already exists.
+    /// The inputs and options of an existing media are replaced, so applying twice is harmless.
+    pub fn apply(&self, instance: &Instance) -> Result<(), VlmError> {
+        self.validate()?;
+
+        let mux = self.mux.as_deref().unwrap_or("");
+        match existing_type(instance, &self.name)? {
+            Some(VlmMediaType::Vod) => {
+                // change_media replaces the inputs and options, the output and loop are unused by a VOD.
+                instance.change_media(&self.name, &self.inputs[0], "", Some(self.options.clone()), self.enabled, false)?;
+                // An empty muxer resets the one of the existing media.
+                instance.set_mux(&self.name, mux)?;
+            },
+            existing => {
+                if existing.is_some() {
+                    instance.del_media(&self.name)?;
+                }
+                instance.add_vod(&self.name, &self.inputs[0], mux, Some(self.options.clone()), self.enabled)?;
+            },
+        }
+        add_inputs(instance, &self.name, &self.inputs[1..])
+    }
+}
+
+// VLM names are used in VLM commands, so they must be single words.
+fn validate_name(name: &str) -> Result<(), VlmError> {
+    if name.is_empty() || name.contains(char::is_whitespace) || name.contains('\0') {
+        Err(VlmError::InvalidArgument("name"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_inputs(inputs: &[String]) -> Result<(), VlmError> {
+    if inputs.is_empty() || inputs.iter().any(|input| input.is_empty() || input.contains('\0')) {
+        Err(VlmError::InvalidArgument("inputs"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_options(options: &[String]) -> Result<(), VlmError> {
+    if options.iter().any(|option| option.is_empty() || option.contains('\0')) {
+        Err(VlmError::InvalidArgument("options"))
+    } else {
+        Ok(())
+    }
+}
+
+// Returns the type of the media of this name, or None if it does not exist.
+fn existing_type(instance: &Instance, name: &str) -> Result<Option<VlmMediaType>, VlmError> {
+    match instance.show_media(name) {
+        Ok(show) => VlmMediaInfo::parse(&show).map(|info| Some(info.media_type)),
+        Err(VlmError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn add_inputs(instance: &Instance, name: &str, inputs: &[String]) -> Result<(), VlmError> {
+    for input in inputs {
+        instance.add_input(name, input)?;
+    }
+    Ok(())
+}
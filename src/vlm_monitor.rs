@@ -0,0 +1,263 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use libc::c_void;
+use crate::sys;
+use crate::Instance;
+use crate::core::{Event, conv_event};
+use crate::enums::EventType;
+use crate::vlm::{Vlm, VlmError, VlmInstanceStatus, vlm_failed};
+
+// Name of a VLM media instance without a name, as shown by libvlc.
+const DEFAULT_INSTANCE: &str = "default";
+
+const VLM_EVENTS: &[EventType] = &[
+    EventType::VlmMediaAdded,
+    EventType::VlmMediaRemoved,
+    EventType::VlmMediaChanged,
+    EventType::VlmMediaInstanceStarted,
+    EventType::VlmMediaInstanceStopped,
+    EventType::VlmMediaInstanceStatusInit,
+    EventType::VlmMediaInstanceStatusOpening,
+    EventType::VlmMediaInstanceStatusPlaying,
+    EventType::VlmMediaInstanceStatusPause,
+    EventType::VlmMediaInstanceStatusEnd,
+    EventType::VlmMediaInstanceStatusError,
+];
+
+/// Change of the VLM state, see VlmMonitor::subscribe().
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VlmChange {
+    MediaAdded { media: String },
+    MediaRemoved { media: String },
+    MediaChanged { media: String },
+    InstanceStarted { media: String, instance: String },
+    InstanceStopped { media: String, instance: String },
+    StatusChanged { media: String, instance: String, status: VlmInstanceStatus },
+}
+
+/// Statuses of the instances of each media, by media name then instance name.
+pub type VlmState = BTreeMap<String, BTreeMap<String, VlmInstanceStatus>>;
+
+struct MonitorData {
+    state: Mutex<VlmState>,
+    subscribers: Mutex<Vec<Sender<VlmChange>>>,
+}
+
+impl MonitorData {
+    fn handle(&self, event: Event) {
+        let change = match event {
+            Event::VlmMediaAdded(_, Some(media)) => VlmChange::MediaAdded{ media },
+            Event::VlmMediaRemoved(_, Some(media)) => VlmChange::MediaRemoved{ media },
+            Event::VlmMediaChanged(_, Some(media)) => VlmChange::MediaChanged{ media },
+            Event::VlmMediaInstanceStarted(instance, Some(media)) =>
+                VlmChange::InstanceStarted{ media, instance: instance_name(instance) },
+            Event::VlmMediaInstanceStopped(instance, Some(media)) =>
+                VlmChange::InstanceStopped{ media, instance: instance_name(instance) },
+            Event::VlmMediaInstanceStatusInit(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::Init),
+            Event::VlmMediaInstanceStatusOpening(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::Opening),
+            Event::VlmMediaInstanceStatusPlaying(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::Playing),
+            Event::VlmMediaInstanceStatusPause(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::Pause),
+            Event::VlmMediaInstanceStatusEnd(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::End),
+            Event::VlmMediaInstanceStatusError(instance, Some(media)) => status_changed(media, instance, VlmInstanceStatus::Error),
+            _ => return,
+        };
+        self.apply(change);
+    }
+
+    fn apply(&self, change: VlmChange) {
+        {
+            let mut state = self.state.lock().unwrap();
+            match change {
+                VlmChange::MediaAdded{ ref media } | VlmChange::MediaChanged{ ref media } => {
+                    state.entry(media.clone()).or_default();
+                },
+                VlmChange::MediaRemoved{ ref media } => {
+                    state.remove(media);
+                },
+                VlmChange::InstanceStarted{ ref media, ref instance } => {
+                    state.entry(media.clone()).or_default().insert(instance.clone(), VlmInstanceStatus::Init);
+                },
+                VlmChange::InstanceStopped{ ref media, ref instance } => {
+                    if let Some(instances) = state.get_mut(media) {
+                        instances.remove(instance);
+                    }
+                },
+                VlmChange::StatusChanged{ ref media, ref instance, status } => {
+                    state.entry(media.clone()).or_default().insert(instance.clone(), status);
+                },
+            }
+        }
+
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(change.clone()).is_ok());
+    }
+}
+
+fn instance_name(instance: Option<String>) -> String {
+    instance.unwrap_or_else(|| DEFAULT_INSTANCE.to_owned())
+}
+
+fn status_changed(media: String, instance: Option<String>, status: VlmInstanceStatus) -> VlmChange {
+    VlmChange::StatusChanged{ media, instance: instance_name(instance), status }
+}
+
+/// Keeps the status of every VLM media instance up to date from the VLM events.
+///
+/// Media created before the monitor are unknown until an event is received for them,
+/// or until they are loaded with refresh().
+pub struct VlmMonitor {
+    instance: Instance,
+    data: *mut MonitorData,
+}
+
+unsafe impl Send for VlmMonitor {}
+unsafe impl Sync for VlmMonitor {}
+
+impl VlmMonitor {
+    /// Start monitoring the VLM events of the instance.
+    pub fn new(instance: &Instance) -> Result<VlmMonitor, VlmError> {
+        let data = Box::into_raw(Box::new(MonitorData {
+            state: Mutex::new(BTreeMap::new()),
+            subscribers: Mutex::new(Vec::new()),
+        }));
+
+        unsafe{
+            let em = sys::libvlc_vlm_get_event_manager(instance.ptr);
+            if em.is_null() {
+                drop(Box::from_raw(data));
+                return Err(vlm_failed());
+            }
+            for (i, &event_type) in VLM_EVENTS.iter().enumerate() {
+                if sys::libvlc_event_attach(em, event_type as i32, vlm_monitor_cb, data as *mut c_void) != 0 {
+                    for &attached in &VLM_EVENTS[..i] {
+                        sys::libvlc_event_detach(em, attached as i32, vlm_monitor_cb, data as *mut c_void);
+                    }
+                    drop(Box::from_raw(data));
+                    return Err(vlm_failed());
+                }
+            }
+        }
+        Ok(VlmMonitor{ instance: instance.clone(), data })
+    }
+
+    fn data(&self) -> &MonitorData {
+        unsafe{ &*self.data }
+    }
+
+    /// Returns a receiver of the changes which happen from now on.
+    pub fn subscribe(&self) -> Receiver<VlmChange> {
+        let (tx, rx) = mpsc::channel();
+        self.data().subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Load the current instances of the named media with Vlm::show_media_info().
+    pub fn refresh(&self, media: &str) -> Result<(), VlmError> {
+        let infos = self.instance.show_media_info(media)?;
+
+        let mut state = self.data().state.lock().unwrap();
+        for info in infos {
+            let instances = info.instances.into_iter()
+                .filter_map(|i| i.status.map(|status| (i.name, status)))
+                .collect();
+            state.insert(info.name, instances);
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the known media.
+    pub fn media(&self) -> Vec<String> {
+        self.data().state.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Returns the statuses of the instances of the media, or None if the media is unknown.
+    pub fn instances(&self, media: &str) -> Option<BTreeMap<String, VlmInstanceStatus>> {
+        self.data().state.lock().unwrap().get(media).cloned()
+    }
+
+    /// Returns the status of a media instance. An unnamed instance is named "default".
+    pub fn status(&self, media: &str, instance: &str) -> Option<VlmInstanceStatus> {
+        self.data().state.lock().unwrap().get(media)?.get(instance).cloned()
+    }
+
+    /// Returns a copy of the whole state.
+    pub fn snapshot(&self) -> VlmState {
+        self.data().state.lock().unwrap().clone()
+    }
+}
+
+impl Drop for VlmMonitor {
+    fn drop(&mut self) {
+        unsafe{
+            let em = sys::libvlc_vlm_get_event_manager(self.instance.ptr);
+            for &event_type in VLM_EVENTS {
+                sys::libvlc_event_detach(em, event_type as i32, vlm_monitor_cb, self.data as *mut c_void);
+            }
+            drop(Box::from_raw(self.data));
+        }
+    }
+}
+
+unsafe extern "C" fn vlm_monitor_cb(pe: *const sys::libvlc_event_t, data: *mut c_void) {
+    let data = &*(data as *const MonitorData);
+    let event = conv_event(pe);
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| data.handle(event)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor_data() -> MonitorData {
+        MonitorData{ state: Mutex::new(BTreeMap::new()), subscribers: Mutex::new(Vec::new()) }
+    }
+
+    fn status(data: &MonitorData, media: &str, instance: &str) -> Option<VlmInstanceStatus> {
+        data.state.lock().unwrap().get(media)?.get(instance).cloned()
+    }
+
+    #[test]
+    fn instance_lifecycle() {
+        let data = monitor_data();
+        let channel = || Some("channel".to_owned());
+
+        data.handle(Event::VlmMediaAdded(None, channel()));
+        assert!(data.state.lock().unwrap()["channel"].is_empty());
+
+        data.handle(Event::VlmMediaInstanceStarted(None, channel()));
+        assert_eq!(status(&data, "channel", "default"), Some(VlmInstanceStatus::Init));
+        data.handle(Event::VlmMediaInstanceStatusPlaying(None, channel()));
+        data.handle(Event::VlmMediaInstanceStatusOpening(Some("backup".to_owned()), channel()));
+        assert_eq!(status(&data, "channel", "default"), Some(VlmInstanceStatus::Playing));
+        assert_eq!(status(&data, "channel", "backup"), Some(VlmInstanceStatus::Opening));
+
+        data.handle(Event::VlmMediaInstanceStopped(None, channel()));
+        assert_eq!(status(&data, "channel", "default"), None);
+
+        data.handle(Event::VlmMediaRemoved(None, channel()));
+        assert!(data.state.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn notifications() {
+        let data = monitor_data();
+        let (tx, rx) = mpsc::channel();
+        data.subscribers.lock().unwrap().push(tx);
+        let (closed, _) = mpsc::channel();
+        data.subscribers.lock().unwrap().push(closed);
+
+        data.handle(Event::VlmMediaInstanceStatusError(None, Some("channel".to_owned())));
+        data.handle(Event::MediaPlayerPlaying);
+        assert_eq!(rx.try_recv(), Ok(VlmChange::StatusChanged {
+            media: "channel".to_owned(), instance: "default".to_owned(), status: VlmInstanceStatus::Error,
+        }));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(data.subscribers.lock().unwrap().len(), 1);
+    }
+}
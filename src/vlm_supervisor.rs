@@ -0,0 +1,471 @@
+// Copyright (c) 2015 T. Okubo
+// This file is part of vlc-rs.
+// Licensed under the MIT license, see the LICENSE file.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::Instance;
+use crate::vlm::{Vlm, VlmError, VlmInstanceStatus};
+use crate::vlm_monitor::{VlmChange, VlmMonitor};
+
+// Name of the instance started by Vlm::play_media().
+const DEFAULT_INSTANCE: &str = "default";
+
+// Number of interventions kept until they are read from the SupervisorHandle.
+const INTERVENTION_CAPACITY: usize = 64;
+
+/// How a supervised broadcast is restarted, see Supervisor::watch().
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RestartPolicy {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_restarts: Option<u32>,
+    stall_timeout: Option<Duration>,
+    restart_on_end: bool,
+    reset_after: Duration,
+    backup_inputs: Vec<String>,
+    failover_after: u32,
+}
+
+impl RestartPolicy {
+    /// Restart without limit, waiting from 1 second up to 1 minute between restarts.
+    /// A broadcast is stalled when its time does not progress for 10 seconds.
+    pub fn new() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_restarts: None,
+            stall_timeout: Some(Duration::from_secs(10)),
+            restart_on_end: true,
+            reset_after: Duration::from_secs(60),
+            backup_inputs: Vec::new(),
+            failover_after: 3,
+        }
+    }
+
+    /// Set the delay before the first restart. It doubles at each restart, up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> RestartPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Give up after `count` consecutive restarts.
+    pub fn max_restarts(mut self, count: u32) -> RestartPolicy {
+        self.max_restarts = Some(count);
+        self
+    }
+
+    /// Set the delay after which a playing broadcast whose time does not progress is restarted.
+    /// None disables the stall detection.
+    pub fn stall_timeout(mut self, timeout: Option<Duration>) -> RestartPolicy {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Restart the broadcast when it reaches its end. Enabled by default.
+    pub fn restart_on_end(mut self, restart: bool) -> RestartPolicy {
+        self.restart_on_end = restart;
+        self
+    }
+
+    /// Set how long a broadcast must play to reset the count of consecutive restarts.
+    pub fn reset_after(mut self, duration: Duration) -> RestartPolicy {
+        self.reset_after = duration;
+        self
+    }
+
+    /// Add a backup input. After every `failover_after` consecutive restarts,
+    /// the input is replaced by the next backup input with Vlm::set_input().
+    pub fn backup_input(mut self, mrl: &str) -> RestartPolicy {
+        self.backup_inputs.push(mrl.to_owned());
+        self
+    }
+
+    /// Set the number of consecutive restarts before failing over to a backup input.
+    pub fn failover_after(mut self, count: u32) -> RestartPolicy {
+        self.failover_after = count.max(1);
+        self
+    }
+
+    // Delay before the nth restart.
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.checked_mul(factor).unwrap_or(self.max_backoff).min(self.max_backoff)
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> RestartPolicy {
+        RestartPolicy::new()
+    }
+}
+
+/// Why a broadcast was restarted.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RestartReason {
+    Error,
+    End,
+    Stalled,
+}
+
+/// Action taken by the supervisor, see SupervisorHandle.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Intervention {
+    /// The broadcast was stopped and played again.
+    Restart { media: String, attempt: u32, reason: RestartReason },
+    /// The input of the broadcast was replaced by a backup input.
+    Failover { media: String, input: String },
+    /// The maximum number of restarts was reached. The broadcast is not supervised anymore.
+    GaveUp { media: String, restarts: u32 },
+    /// A libvlc call failed during an intervention.
+    Failed { media: String, error: VlmError },
+}
+
+/// Supervises VLM broadcasts and restarts them when they fail or stall.
+pub struct Supervisor {
+    instance: Instance,
+    watches: Vec<(String, RestartPolicy)>,
+    check_interval: Duration,
+}
+
+impl Supervisor {
+    pub fn new(instance: &Instance) -> Supervisor {
+        Supervisor{ instance: instance.clone(), watches: Vec::new(), check_interval: Duration::from_secs(1) }
+    }
+
+    /// Supervise the named broadcast.
+    pub fn watch(mut self, name: &str, policy: RestartPolicy) -> Supervisor {
+        self.watches.push((name.to_owned(), policy));
+        self
+    }
+
+    /// Set the interval at which scheduled restarts and stalls are checked
+    /// when no VLM event is received. The default is 1 second.
+    pub fn check_interval(mut self, interval: Duration) -> Supervisor {
+        self.check_interval = interval;
+        self
+    }
+
+    /// Start supervising in a background thread.
+    /// The returned handle yields the interventions, and stops the supervision when dropped.
+    pub fn start(self) -> Result<SupervisorHandle, VlmError> {
+        let monitor = VlmMonitor::new(&self.instance)?;
+        let changes = monitor.subscribe();
+        let mut watches = Vec::with_capacity(self.watches.len());
+        for (name, policy) in &self.watches {
+            let _ = monitor.refresh(name);
+            let mut watch = Watch::new(name.clone(), policy.clone());
+            watch.status = monitor.status(name, DEFAULT_INSTANCE);
+            watches.push(watch);
+        }
+
+        let (tx, rx) = mpsc::sync_channel(INTERVENTION_CAPACITY);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stop = stopped.clone();
+        thread::spawn(move || {
+            // The monitor must live as long as its subscription.
+            let _monitor = monitor;
+            self.run(watches, &changes, &tx, &stop)
+        });
+
+        Ok(SupervisorHandle{ rx, stopped })
+    }
+
+    fn run(self, mut watches: Vec<Watch>, changes: &Receiver<VlmChange>, tx: &SyncSender<Intervention>, stopped: &AtomicBool) {
+        while !stopped.load(Ordering::SeqCst) {
+            let changed = match changes.recv_timeout(self.check_interval) {
+                Ok(change) => Some(change),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            let now = Instant::now();
+            for watch in &mut watches {
+                if let Some(ref change) = changed {
+                    watch.update(change);
+                }
+                let time = match watch.status {
+                    Some(VlmInstanceStatus::Playing) => self.instance.get_media_instance_time(&watch.name, 0).ok(),
+                    _ => None,
+                };
+                for action in watch.observe(now, watch.status, time) {
+                    // Interventions are dropped while the handle is not read.
+                    let _ = tx.try_send(perform(&self.instance, &watch.name, action));
+                }
+            }
+        }
+    }
+}
+
+fn perform(instance: &Instance, name: &str, action: Action) -> Intervention {
+    let media = name.to_owned();
+    let result = match action {
+        Action::Failover(input) => instance.set_input(name, &input)
+            .map(|_| Intervention::Failover{ media: media.clone(), input }),
+        Action::Restart{ attempt, reason } => {
+            // The broadcast may already be stopped.
+            let _ = instance.stop_media(name);
+            instance.play_media(name)
+                .map(|_| Intervention::Restart{ media: media.clone(), attempt, reason })
+        },
+        Action::GiveUp{ restarts } => Ok(Intervention::GaveUp{ media: media.clone(), restarts }),
+    };
+    result.unwrap_or_else(|error| Intervention::Failed{ media, error })
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Action {
+    Restart { attempt: u32, reason: RestartReason },
+    Failover(String),
+    GiveUp { restarts: u32 },
+}
+
+// Supervision state of a broadcast.
+struct Watch {
+    name: String,
+    policy: RestartPolicy,
+    // Status of the default instance, from the VLM events.
+    status: Option<VlmInstanceStatus>,
+    // Consecutive restarts.
+    attempts: u32,
+    // Scheduled restart.
+    pending: Option<(Instant, RestartReason)>,
+    // Last time of the broadcast, and when it was observed first.
+    last_time: Option<(i32, Instant)>,
+    healthy_since: Option<Instant>,
+    next_backup: usize,
+    gave_up: bool,
+}
+
+impl Watch {
+    fn new(name: String, policy: RestartPolicy) -> Watch {
+        Watch {
+            name,
+            policy,
+            status: None,
+            attempts: 0,
+            pending: None,
+            last_time: None,
+            healthy_since: None,
+            next_backup: 0,
+            gave_up: false,
+        }
+    }
+
+    // Update the status of the default instance from a VLM change.
+    fn update(&mut self, change: &VlmChange) {
+        match *change {
+            VlmChange::InstanceStarted{ ref media, ref instance } if *media == self.name && instance == DEFAULT_INSTANCE => {
+                self.status = Some(VlmInstanceStatus::Init);
+            },
+            VlmChange::InstanceStopped{ ref media, ref instance } if *media == self.name && instance == DEFAULT_INSTANCE => {
+                self.status = None;
+            },
+            VlmChange::StatusChanged{ ref media, ref instance, status } if *media == self.name && instance == DEFAULT_INSTANCE => {
+                self.status = Some(status);
+            },
+            VlmChange::MediaRemoved{ ref media } if *media == self.name => {
+                self.status = None;
+            },
+            _ => (),
+        }
+    }
+
+    // Returns the actions to take for the status and time of the broadcast at `now`.
+    fn observe(&mut self, now: Instant, status: Option<VlmInstanceStatus>, time: Option<i32>) -> Vec<Action> {
+        if self.gave_up {
+            return Vec::new();
+        }
+
+        if let Some((at, reason)) = self.pending {
+            if now < at {
+                return Vec::new();
+            }
+            self.pending = None;
+            self.last_time = None;
+
+            let mut actions = Vec::new();
+            if !self.policy.backup_inputs.is_empty() && self.attempts.is_multiple_of(self.policy.failover_after) {
+                let input = &self.policy.backup_inputs[self.next_backup % self.policy.backup_inputs.len()];
+                self.next_backup += 1;
+                actions.push(Action::Failover(input.clone()));
+            }
+            actions.push(Action::Restart{ attempt: self.attempts, reason });
+            return actions;
+        }
+
+        let reason = match status {
+            Some(VlmInstanceStatus::Error) => Some(RestartReason::Error),
+            Some(VlmInstanceStatus::End) if self.policy.restart_on_end => Some(RestartReason::End),
+            Some(VlmInstanceStatus::Playing) => self.check_stall(now, time),
+            _ => {
+                self.last_time = None;
+                None
+            },
+        };
+
+        match reason {
+            Some(reason) => {
+                self.healthy_since = None;
+                if self.policy.max_restarts.is_some_and(|max| self.attempts >= max) {
+                    self.gave_up = true;
+                    return vec![Action::GiveUp{ restarts: self.attempts }];
+                }
+                self.attempts += 1;
+                self.pending = Some((now + self.policy.delay(self.attempts), reason));
+            },
+            None if status == Some(VlmInstanceStatus::Playing) => {
+                let since = *self.healthy_since.get_or_insert(now);
+                if now.duration_since(since) >= self.policy.reset_after {
+                    self.attempts = 0;
+                }
+            },
+            None => (),
+        }
+        Vec::new()
+    }
+
+    fn check_stall(&mut self, now: Instant, time: Option<i32>) -> Option<RestartReason> {
+        let (timeout, time) = match (self.policy.stall_timeout, time) {
+            (Some(timeout), Some(time)) => (timeout, time),
+            _ => return None,
+        };
+        match self.last_time {
+            Some((last, since)) if last == time => {
+                if now.duration_since(since) >= timeout { Some(RestartReason::Stalled) } else { None }
+            },
+            _ => {
+                self.last_time = Some((time, now));
+                None
+            },
+        }
+    }
+}
+
+/// Handle to a running supervision. Iterating over it yields the interventions until it is stopped.
+///
+/// Up to 64 interventions are kept until they are read, the next ones are dropped.
+pub struct SupervisorHandle {
+    rx: Receiver<Intervention>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl SupervisorHandle {
+    /// Returns the next intervention if there is one, without blocking.
+    pub fn try_next(&self) -> Option<Intervention> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Stop the supervision. The broadcasts are left as they are.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Iterator for SupervisorHandle {
+    type Item = Intervention;
+
+    fn next(&mut self) -> Option<Intervention> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for SupervisorHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYING: Option<VlmInstanceStatus> = Some(VlmInstanceStatus::Playing);
+    const ERROR: Option<VlmInstanceStatus> = Some(VlmInstanceStatus::Error);
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn restart_with_backoff() {
+        let policy = RestartPolicy::new().backoff(secs(1), secs(3)).max_restarts(3);
+        let mut watch = Watch::new("channel".to_owned(), policy);
+        let t0 = Instant::now();
+        let restart = |attempt| vec![Action::Restart{ attempt, reason: RestartReason::Error }];
+
+        assert!(watch.observe(t0, ERROR, None).is_empty());
+        assert_eq!(watch.observe(t0 + secs(1), ERROR, None), restart(1));
+
+        assert!(watch.observe(t0 + secs(2), ERROR, None).is_empty());
+        assert!(watch.observe(t0 + secs(3), ERROR, None).is_empty());
+        assert_eq!(watch.observe(t0 + secs(4), ERROR, None), restart(2));
+
+        assert!(watch.observe(t0 + secs(5), ERROR, None).is_empty());
+        assert!(watch.observe(t0 + secs(7), ERROR, None).is_empty());
+        assert_eq!(watch.observe(t0 + secs(8), ERROR, None), restart(3));
+
+        assert_eq!(watch.observe(t0 + secs(9), ERROR, None), vec![Action::GiveUp{ restarts: 3 }]);
+        assert!(watch.observe(t0 + secs(60), ERROR, None).is_empty());
+    }
+
+    #[test]
+    fn stall_and_failover() {
+        let policy = RestartPolicy::new()
+            .backoff(secs(0), secs(0))
+            .stall_timeout(Some(secs(10)))
+            .backup_input("file:///backup.ts")
+            .failover_after(1);
+        let mut watch = Watch::new("channel".to_owned(), policy);
+        let t0 = Instant::now();
+
+        assert!(watch.observe(t0, PLAYING, Some(1000)).is_empty());
+        assert!(watch.observe(t0 + secs(5), PLAYING, Some(6000)).is_empty());
+        assert!(watch.observe(t0 + secs(14), PLAYING, Some(6000)).is_empty());
+        assert!(watch.observe(t0 + secs(15), PLAYING, Some(6000)).is_empty());
+        assert_eq!(watch.observe(t0 + secs(15), PLAYING, Some(6000)), vec![
+            Action::Failover("file:///backup.ts".to_owned()),
+            Action::Restart{ attempt: 1, reason: RestartReason::Stalled },
+        ]);
+    }
+
+    #[test]
+    fn healthy_playback_resets_attempts() {
+        let policy = RestartPolicy::new().backoff(secs(0), secs(0)).reset_after(secs(30));
+        let mut watch = Watch::new("channel".to_owned(), policy);
+        let t0 = Instant::now();
+
+        watch.observe(t0, ERROR, None);
+        watch.observe(t0, ERROR, None);
+        assert_eq!(watch.attempts, 1);
+
+        watch.observe(t0 + secs(1), PLAYING, Some(0));
+        watch.observe(t0 + secs(20), PLAYING, Some(19000));
+        assert_eq!(watch.attempts, 1);
+        watch.observe(t0 + secs(31), PLAYING, Some(30000));
+        assert_eq!(watch.attempts, 0);
+    }
+
+    #[test]
+    fn status_from_changes() {
+        let mut watch = Watch::new("channel".to_owned(), RestartPolicy::new());
+        let status = |media: &str, instance: &str, status| VlmChange::StatusChanged {
+            media: media.to_owned(), instance: instance.to_owned(), status,
+        };
+
+        watch.update(&VlmChange::InstanceStarted{ media: "channel".to_owned(), instance: "default".to_owned() });
+        assert_eq!(watch.status, Some(VlmInstanceStatus::Init));
+        watch.update(&status("channel", "default", VlmInstanceStatus::Playing));
+        watch.update(&status("channel", "backup", VlmInstanceStatus::Error));
+        watch.update(&status("other", "default", VlmInstanceStatus::Error));
+        assert_eq!(watch.status, PLAYING);
+
+        watch.update(&VlmChange::InstanceStopped{ media: "channel".to_owned(), instance: "default".to_owned() });
+        assert_eq!(watch.status, None);
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "catalog")]
+
+extern crate vlc;
+
+use std::env;
+use std::fs;
+
+use vlc::{Instance, Meta, Catalog, CatalogEntry, CatalogError, CatalogQuery, MediaTrack, MediaTrackUnion, AudioTrack, TrackType};
+
+fn entry(mrl: &str, artist: &str, album: &str, genre: &str, duration: i64) -> CatalogEntry {
+    let mut entry = CatalogEntry::new(mrl);
+    entry.meta.insert(Meta::Title, mrl.trim_start_matches("file:///").to_owned());
+    entry.meta.insert(Meta::Artist, artist.to_owned());
+    entry.meta.insert(Meta::Album, album.to_owned());
+    entry.meta.insert(Meta::Genre, genre.to_owned());
+    entry.duration = Some(duration);
+    entry
+}
+
+fn catalog() -> Catalog {
+    let mut catalog = Catalog::new();
+    catalog.insert(entry("file:///blue_train.mp3", "John Coltrane", "Blue Train", "Jazz", 643_000));
+    catalog.insert(entry("file:///so_what.mp3", "Miles Davis", "Kind of Blue", "Jazz", 562_000));
+    catalog.insert(entry("file:///clair_de_lune.mp3", "Debussy", "Suite bergamasque", "Classical", 300_000));
+    catalog
+}
+
+fn mrls(entries: Vec<&CatalogEntry>) -> Vec<&str> {
+    entries.into_iter().map(|e| e.mrl.as_str()).collect()
+}
+
+#[test]
+fn search() {
+    let catalog = catalog();
+
+    assert_eq!(mrls(catalog.search(&CatalogQuery::new().text("BLUE"))),
+               ["file:///blue_train.mp3", "file:///so_what.mp3"]);
+    assert_eq!(mrls(catalog.search(&CatalogQuery::new().genre("jazz").artist("miles davis"))),
+               ["file:///so_what.mp3"]);
+    assert_eq!(mrls(catalog.search(&CatalogQuery::new().album("blue train"))),
+               ["file:///blue_train.mp3"]);
+    assert_eq!(mrls(catalog.search(&CatalogQuery::new().duration(0..=600_000))),
+               ["file:///so_what.mp3", "file:///clair_de_lune.mp3"]);
+    assert!(catalog.search(&CatalogQuery::new().text("blue").genre("classical")).is_empty());
+    assert_eq!(catalog.search(&CatalogQuery::new()).len(), 3);
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut catalog = catalog();
+
+    catalog.record_play("file:///so_what.mp3").unwrap();
+    catalog.set_last_position("file:///so_what.mp3", Some(1000)).unwrap();
+    catalog.insert(entry("file:///so_what.mp3", "Miles Davis", "Kind of Blue", "Modal jazz", 562_000));
+    let so_what = catalog.get("file:///so_what.mp3").unwrap();
+    assert_eq!(so_what.meta(Meta::Genre), Some("Modal jazz"));
+    assert_eq!(so_what.play_count, 1);
+    assert_eq!(so_what.last_position, Some(1000));
+    assert_eq!(catalog.len(), 3);
+
+    assert!(catalog.remove("file:///blue_train.mp3").is_some());
+    assert!(catalog.get("file:///blue_train.mp3").is_none());
+    assert_eq!(catalog.get("file:///clair_de_lune.mp3").unwrap().meta(Meta::Artist), Some("Debussy"));
+    assert_eq!(catalog.record_play("file:///blue_train.mp3"),
+               Err(CatalogError::NotFound("file:///blue_train.mp3".to_owned())));
+}
+
+#[test]
+fn save_and_open() {
+    let path = env::temp_dir().join(format!("vlc-rs-catalog-{}.json", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut catalog = Catalog::open(&path).unwrap();
+    assert!(catalog.is_empty());
+    for e in self::catalog().entries() {
+        catalog.insert(e.clone());
+    }
+    let mut with_track = CatalogEntry::new("file:///a_love_supreme.flac");
+    with_track.tracks.push(MediaTrack {
+        codec: 0x63616c66,
+        original_fourcc: 0,
+        id: 0,
+        track_type: TrackType::Audio,
+        profile: -1,
+        level: -1,
+        bitrate: 0,
+        language: Some("eng".to_owned()),
+        description: None,
+        type_specific_data: MediaTrackUnion::Audio(AudioTrack{ channels: 2, rate: 44100 }),
+    });
+    catalog.insert(with_track);
+    catalog.record_play("file:///clair_de_lune.mp3").unwrap();
+    catalog.save().unwrap();
+
+    let reopened = Catalog::open(&path).unwrap();
+    assert_eq!(reopened.entries(), catalog.entries());
+    assert_eq!(reopened.get("file:///clair_de_lune.mp3").unwrap().play_count, 1);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn to_media_list() {
+    let instance = Instance::new().unwrap();
+    let catalog = catalog();
+
+    let results = catalog.search(&CatalogQuery::new().genre("jazz"));
+    let list = Catalog::to_media_list(&instance, &results).unwrap();
+    list.lock();
+    assert_eq!(list.count(), 2);
+    assert_eq!(list.item_at_index(1).unwrap().mrl().as_deref(), Some("file:///so_what.mp3"));
+    list.unlock();
+}
@@ -0,0 +1,41 @@
+extern crate vlc;
+
+use std::env;
+use std::fs;
+use vlc::{Instance, Media, ClipExporter, ClipMode, ClipError, TranscodePreset, Transcoder};
+
+// 2 seconds of a 440 Hz tone, 8 kHz mono.
+const TONE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tone.wav");
+
+#[test]
+fn clip_options() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, "file:///input.mkv").unwrap();
+
+    let clip = ClipExporter::new(&instance, &md, 1500, 10_000, "clip.mp4");
+    assert_eq!(clip.transcoder().sout().to_string(), "#std{access=file,dst=clip.mp4}");
+
+    let clip = clip.mode(ClipMode::Preset(TranscodePreset::H264Mp4));
+    assert!(clip.transcoder().sout().to_string().starts_with("#transcode{vcodec=h264"));
+
+    assert_eq!(ClipExporter::new(&instance, &md, 5000, 5000, "clip.mp4").run().err(), Some(ClipError::InvalidRange));
+}
+
+// Needs libvlc with its plugins.
+#[test]
+#[ignore]
+fn export_fixture() {
+    let instance = Transcoder::instance().unwrap();
+    let output = env::temp_dir().join(format!("vlc-rs-clip-{}.flac", std::process::id()));
+
+    let md = Media::new_path(&instance, TONE).unwrap();
+    let clip = ClipExporter::new(&instance, &md, 500, 1500, &output).mode(ClipMode::Preset(TranscodePreset::Flac));
+    let mut handle = clip.start().unwrap();
+    let progress: Vec<_> = handle.by_ref().collect();
+    assert!(progress.iter().all(|p| (0.0..=1.0).contains(&p.fraction)));
+
+    let result = handle.wait().unwrap();
+    assert!((result.duration - 1000).abs() <= 200);
+    assert!(md.applied_options().is_empty());
+    fs::remove_file(&output).unwrap();
+}
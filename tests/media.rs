@@ -0,0 +1,170 @@
+extern crate vlc;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vlc::{Instance, Media, MediaMetadata, MediaOption, MediaOptions, MetaDate, Meta, ParsedStatus};
+
+const MRL: &str = "file:///dev/null";
+
+// Increments the counter when dropped.
+struct DropCounter(Arc<AtomicUsize>, u32);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn user_data() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    assert!(md.user_data::<DropCounter>().is_none());
+    md.set_user_data(DropCounter(dropped.clone(), 1));
+    assert_eq!(md.user_data::<DropCounter>().unwrap().1, 1);
+    assert!(md.user_data::<u32>().is_none());
+
+    md.set_user_data(DropCounter(dropped.clone(), 2));
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+    assert_eq!(md.clone().user_data::<DropCounter>().unwrap().1, 2);
+
+    drop(md);
+    assert_eq!(dropped.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn clear_user_data() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    md.set_user_data(DropCounter(dropped.clone(), 1));
+    md.clear_user_data();
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+    assert!(md.user_data::<DropCounter>().is_none());
+}
+
+#[test]
+fn applied_options() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    md.add_option(":no-video");
+    md.add_option(":no-video");
+    md.add_options(&MediaOptions::new().network_caching(300).option(":http-reconnect", false));
+    md.add_options(&MediaOptions::new().sout("#display").sout("#std{dst=out.ts}").trusted().sub_file("a.srt"));
+
+    let option = |option: &str, trusted, unique| MediaOption{ option: option.to_owned(), trusted, unique };
+    assert_eq!(md.applied_options(), vec![
+        option(":no-video", true, true),
+        option(":network-caching=300", true, false),
+        option(":http-reconnect", false, false),
+        option(":sout=#display", false, false),
+        option(":sout=#std{dst=out.ts}", true, false),
+        option(":sub-file=a.srt", false, false),
+    ]);
+}
+
+#[test]
+fn duplicate() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    md.set_user_data(1u32);
+
+    let dup = md.duplicate().unwrap();
+    assert_ne!(dup.raw(), md.raw());
+    assert_eq!(dup.mrl().as_deref(), Some(MRL));
+    assert!(dup.user_data::<u32>().is_none());
+
+    md.add_option(":no-audio");
+    assert_eq!(md.duplicate().unwrap().applied_options(), md.applied_options());
+
+    let md = Media::from_reader(&instance, std::io::Cursor::new(vec![0u8; 16])).unwrap();
+    assert!(md.duplicate().is_none());
+}
+
+#[test]
+fn parse_wait() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    assert_eq!(md.parse_wait(&[], 0), Some(ParsedStatus::Done));
+    // libvlc ignores the second request, the status of the first one is returned.
+    assert_eq!(md.parse_wait(&[], 0), Some(ParsedStatus::Done));
+
+    let md = Media::new_location(&instance, MRL).unwrap();
+    md.parse_async();
+    assert_eq!(md.parse_wait(&[], 1000), Some(ParsedStatus::Done));
+}
+
+#[test]
+fn expand() {
+    let instance = Instance::new().unwrap();
+    let root = Media::new_node(&instance, "root").unwrap();
+    let node = Media::new_node(&instance, "node").unwrap();
+    let playlist = Media::new_location(&instance, "file:///playlist.m3u").unwrap();
+    let a = Media::new_location(&instance, "file:///a.mp3").unwrap();
+    let b = Media::new_location(&instance, "file:///b.mp3").unwrap();
+    let c = Media::new_location(&instance, "file:///c.mp3").unwrap();
+
+    let add = |parent: &Media, md: &Media| {
+        let list = parent.subitems().unwrap();
+        list.lock();
+        list.add_media(md).unwrap();
+        list.unlock();
+    };
+    add(&root, &a);
+    add(&root, &node);
+    add(&node, &b);
+    add(&node, &root);
+    add(&node, &playlist);
+    add(&playlist, &c);
+    // Another media for the same playlist file.
+    add(&playlist, &Media::new_location(&instance, "file:///playlist.m3u").unwrap());
+
+    let mrls = |tree: &vlc::MediaTree| -> Vec<String> {
+        tree.flatten().iter().map(|md| md.mrl().unwrap()).collect()
+    };
+
+    let tree = root.expand(10);
+    assert_eq!(tree.children.len(), 2);
+    assert_eq!(tree.children[1].children.len(), 2);
+    assert_eq!(mrls(&tree), ["file:///a.mp3", "file:///b.mp3", "file:///c.mp3"]);
+
+    let tree = root.expand(2);
+    assert_eq!(mrls(&tree), ["file:///a.mp3", "file:///b.mp3", "file:///playlist.m3u"]);
+
+    let tree = root.expand(0);
+    assert!(tree.children.is_empty());
+}
+
+#[test]
+fn metadata() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    // The title falls back to the name of the media.
+    assert_eq!(md.metadata(), MediaMetadata{ title: Some("null".to_owned()), ..MediaMetadata::default() });
+
+    let mut metadata = MediaMetadata::default();
+    metadata.title = Some("Title".to_owned());
+    metadata.track_number = Some(3);
+    metadata.date = Some(MetaDate{ year: 2004, month: Some(5), day: None });
+    // The meta are set, but cannot be saved to /dev/null.
+    assert_eq!(md.set_metadata(&metadata), Err(vec![Meta::Title, Meta::TrackNumber, Meta::Date]));
+
+    assert_eq!(md.get_meta(Meta::TrackNumber).as_deref(), Some("3"));
+    assert_eq!(md.get_meta(Meta::Date).as_deref(), Some("2004-05"));
+    assert_eq!(md.metadata(), metadata);
+
+    md.set_meta(Meta::TrackNumber, "not a number");
+    assert_eq!(md.metadata().track_number, None);
+}
+
+#[test]
+fn meta_date() {
+    assert_eq!("2004".parse(), Ok(MetaDate{ year: 2004, month: None, day: None }));
+    assert_eq!("2004-05-12T10:00:00Z".parse(), Ok(MetaDate{ year: 2004, month: Some(5), day: Some(12) }));
+    assert_eq!("2004-13".parse::<MetaDate>(), Err(()));
+    assert_eq!("May 2004".parse::<MetaDate>(), Err(()));
+}
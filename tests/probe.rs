@@ -0,0 +1,25 @@
+#![cfg(feature = "probe")]
+
+use std::process::Command;
+
+const PROBE: &str = env!("CARGO_BIN_EXE_vlc-probe");
+
+#[test]
+fn usage_error() {
+    let output = Command::new(PROBE).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = Command::new(PROBE).arg("--bogus").arg("a.mp3").output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn missing_file() {
+    let output = Command::new(PROBE).args(&["--json", "/nonexistent/vlc-probe.mp3"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let reports: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(reports[0]["input"], "/nonexistent/vlc-probe.mp3");
+    assert_eq!(reports[0]["playable"], false);
+    assert_eq!(reports[0]["error"], "no such file");
+}
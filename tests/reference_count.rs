@@ -0,0 +1,115 @@
+// Checks that cloned handles and getters hold their own libvlc references.
+// The MediaFreed event is sent when the last reference of a media is released.
+
+extern crate vlc;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use vlc::{Instance, Media, MediaPlayer, MediaList, EventType};
+
+const MRL: &str = "file:///dev/null";
+
+fn count_freed(md: &Media) -> Arc<AtomicUsize> {
+    let freed = Arc::new(AtomicUsize::new(0));
+    let counter = freed.clone();
+    md.event_manager().attach(EventType::MediaFreed, move |_, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    }).unwrap();
+    freed
+}
+
+#[test]
+fn media_clone() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let freed = count_freed(&md);
+
+    let md2 = md.clone();
+    assert_eq!(md2.raw(), md.raw());
+    drop(md);
+    assert_eq!(freed.load(Ordering::SeqCst), 0);
+    assert_eq!(md2.mrl().as_deref(), Some(MRL));
+    drop(md2);
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn instance_clone() {
+    let instance = Instance::new().unwrap();
+    let instance2 = instance.clone();
+    drop(instance);
+
+    let md = Media::new_location(&instance2, MRL).unwrap();
+    assert_eq!(md.mrl().as_deref(), Some(MRL));
+}
+
+#[test]
+fn media_player_get_media() {
+    let instance = Instance::new().unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let freed = count_freed(&md);
+
+    mdp.set_media(&md);
+    drop(md);
+    let md = mdp.get_media().unwrap();
+    drop(mdp);
+    assert_eq!(freed.load(Ordering::SeqCst), 0);
+    drop(md);
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn media_player_clone() {
+    let instance = Instance::new().unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let freed = count_freed(&md);
+
+    mdp.set_media(&md);
+    drop(md);
+    let mdp2 = mdp.clone();
+    drop(mdp);
+    assert_eq!(freed.load(Ordering::SeqCst), 0);
+    drop(mdp2);
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn media_list_clone_and_item_at_index() {
+    let instance = Instance::new().unwrap();
+    let list = MediaList::new(&instance).unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let freed = count_freed(&md);
+
+    list.lock();
+    list.add_media(&md).unwrap();
+    list.unlock();
+    drop(md);
+
+    let list2 = list.clone();
+    drop(list);
+    list2.lock();
+    let item = list2.item_at_index(0).unwrap();
+    list2.unlock();
+    drop(list2);
+    assert_eq!(freed.load(Ordering::SeqCst), 0);
+    drop(item);
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn objects_outlive_instance() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, MRL).unwrap();
+    let mdp = MediaPlayer::new(&instance).unwrap();
+    let list = MediaList::new(&instance).unwrap();
+    drop(instance);
+
+    mdp.set_media(&md);
+    list.lock();
+    list.add_media(&md).unwrap();
+    list.unlock();
+    assert_eq!(mdp.get_media().unwrap().mrl().as_deref(), Some(MRL));
+}
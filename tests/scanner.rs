@@ -0,0 +1,73 @@
+extern crate vlc;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use vlc::{Instance, Scanner, ScanEvent};
+
+fn make_tree(name: &str) -> PathBuf {
+    let root = env::temp_dir().join(format!("vlc-rs-scanner-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("album/disc2")).unwrap();
+    for file in &["a.mp3", "notes.txt", "album/b.FLAC", "album/cover.jpg", "album/disc2/c.ogg", "album/disc2/d.mkv"] {
+        fs::write(root.join(file), b"").unwrap();
+    }
+    root
+}
+
+#[test]
+fn scan_directory() {
+    let root = make_tree("scan");
+    let instance = Instance::new().unwrap();
+    let scanner = Scanner::new(&instance).concurrency(2);
+
+    let mut total = None;
+    let mut paths = Vec::new();
+    let mut last_progress = 0;
+    for event in scanner.scan(&root) {
+        match event {
+            ScanEvent::Started{ total: n } => total = Some(n),
+            // The files are empty, so only the end of parsing is checked, not its status.
+            ScanEvent::Parsed(record) => {
+                paths.push(record.path.strip_prefix(&root).unwrap().to_owned());
+            },
+            ScanEvent::Progress{ done, total } => {
+                assert_eq!(total, 4);
+                last_progress = done;
+            },
+            e => panic!("unexpected event: {:?}", e),
+        }
+    }
+    paths.sort();
+
+    assert_eq!(total, Some(4));
+    assert_eq!(last_progress, 4);
+    let expected: Vec<PathBuf> = ["a.mp3", "album/b.FLAC", "album/disc2/c.ogg", "album/disc2/d.mkv"]
+        .iter().map(PathBuf::from).collect();
+    assert_eq!(paths, expected);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn scan_extensions() {
+    let root = make_tree("ext");
+    let instance = Instance::new().unwrap();
+    let scanner = Scanner::new(&instance).extensions(&["MKV", "txt"]);
+
+    assert!(scanner.is_media_file(&root.join("x.mkv")));
+    assert!(!scanner.is_media_file(&root.join("x.mp3")));
+    let parsed = scanner.scan(&root).filter(|e| matches!(e, ScanEvent::Parsed(_))).count();
+    assert_eq!(parsed, 2);
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn scan_missing_directory() {
+    let instance = Instance::new().unwrap();
+    let events: Vec<_> = Scanner::new(&instance).scan("/nonexistent/vlc-rs").collect();
+
+    assert!(matches!(events[0], ScanEvent::Error{ .. }));
+    assert!(matches!(events[1], ScanEvent::Started{ total: 0 }));
+    assert_eq!(events.len(), 2);
+}
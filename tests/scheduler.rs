@@ -0,0 +1,118 @@
+#![cfg(feature = "scheduler")]
+
+extern crate vlc;
+
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use vlc::{Instance, Vlm, Cron, Schedule, ChannelSchedule, Program, Scheduler, Clock};
+
+// 2024-01-01 00:00 UTC, a Monday.
+const MONDAY: u64 = 1_704_067_200;
+
+fn at(hour: u64, minute: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(MONDAY + hour * 3600 + minute * 60)
+}
+
+#[derive(Clone)]
+struct MockClock(Arc<Mutex<SystemTime>>);
+
+impl MockClock {
+    fn set(&self, time: SystemTime) {
+        *self.0.lock().unwrap() = time;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap()
+    }
+}
+
+fn playout() -> Schedule {
+    Schedule::new(0).channel(
+        ChannelSchedule::new("channel")
+            .slot(Cron::daily(8, 0).unwrap(), 4 * 60, "file:///a.ts")
+            .slot(Cron::daily(12, 30).unwrap(), 90, "file:///b.ts")
+            .filler("file:///filler.ts"))
+}
+
+#[test]
+fn cron_parsing() {
+    assert!("*/15 8-18 * * 1-5".parse::<Cron>().is_ok());
+    assert!("0 0 * * 7".parse::<Cron>().is_ok());
+    assert!("60 * * * *".parse::<Cron>().is_err());
+    assert!("* * *".parse::<Cron>().is_err());
+    // Dates which never occur.
+    assert!("0 0 31 2 *".parse::<Cron>().is_err());
+    assert!("0 0 31 4,6 *".parse::<Cron>().is_err());
+    assert!("0 0 29 2 *".parse::<Cron>().is_ok());
+    assert!("0 0 31 2 1".parse::<Cron>().is_ok());
+    assert_eq!(Cron::daily(12, 30).unwrap().to_string(), "30 12 * * *");
+    assert!(Cron::daily(24, 0).is_err());
+    assert!(Cron::daily(9, 60).is_err());
+
+    // Weekdays only: nothing on Sunday.
+    let weekdays = Schedule::new(0).channel(
+        ChannelSchedule::new("news").slot("0 18 * * 1-5".parse().unwrap(), 30, "file:///news.ts"));
+    assert_eq!(weekdays.programs_at(at(18, 10))[0].1.input(), Some("file:///news.ts"));
+    assert_eq!(weekdays.programs_at(at(6 * 24 + 18, 10))[0].1, Program::Stopped);
+}
+
+#[test]
+fn timeline() {
+    let timeline = playout().timeline(at(0, 0), at(23, 59));
+    let lines: Vec<String> = timeline.iter().map(|entry| entry.to_string()).collect();
+    assert_eq!(lines, vec![
+        "2024-01-01 00:00 channel: file:///filler.ts (filler)",
+        "2024-01-01 08:00 channel: file:///a.ts",
+        "2024-01-01 12:00 channel: file:///filler.ts (filler)",
+        "2024-01-01 12:30 channel: file:///b.ts",
+        "2024-01-01 14:00 channel: file:///filler.ts (filler)",
+    ]);
+
+    // Wall-clock times are shifted by the UTC offset.
+    let mut shifted = playout();
+    shifted.utc_offset = 60;
+    assert_eq!(shifted.programs_at(at(7, 0))[0].1.input(), Some("file:///a.ts"));
+}
+
+#[test]
+fn scheduler_with_mock_clock() {
+    let instance = Instance::new().unwrap();
+    instance.add_broadcast("channel", "file:///filler.ts", "#display", None, true, false).unwrap();
+    let clock = MockClock(Arc::new(Mutex::new(at(7, 59))));
+    let mut scheduler = Scheduler::new(instance.clone(), playout()).clock(clock.clone());
+
+    let mut timeline = Vec::new();
+    scheduler.print_timeline(Duration::from_secs(60), &mut timeline).unwrap();
+    assert_eq!(String::from_utf8(timeline).unwrap(),
+               "2024-01-01 07:59 channel: file:///filler.ts (filler)\n2024-01-01 08:00 channel: file:///a.ts\n");
+
+    let changes = scheduler.tick();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].program, Program::Filler("file:///filler.ts".to_owned()));
+    assert!(changes[0].result.is_ok());
+    assert!(scheduler.tick().is_empty());
+
+    clock.set(at(8, 0));
+    let changes = scheduler.tick();
+    assert_eq!(changes[0].program.input(), Some("file:///a.ts"));
+    assert!(changes[0].result.is_ok());
+
+    let mut dry_run = Scheduler::new(instance.clone(), playout()).clock(clock.clone()).dry_run(true);
+    clock.set(at(13, 0));
+    assert_eq!(dry_run.tick()[0].program.input(), Some("file:///b.ts"));
+}
+
+#[test]
+fn save_and_load() {
+    let path = env::temp_dir().join(format!("vlc-rs-schedule-{}.json", std::process::id()));
+    let mut schedule = playout();
+    schedule.channels[0].slots[1].seek = Some(10.0);
+
+    schedule.save(&path).unwrap();
+    assert_eq!(Schedule::load(&path).unwrap(), schedule);
+    let _ = std::fs::remove_file(&path);
+}
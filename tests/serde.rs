@@ -0,0 +1,35 @@
+#![cfg(feature = "serde")]
+
+extern crate vlc;
+
+use vlc::{Event, Meta, State, TrackType, AudioTrack, MediaTrack, MediaTrackUnion};
+
+#[test]
+fn event_round_trip() {
+    let events = vec![
+        Event::MediaStateChanged(State::Playing),
+        Event::MediaMetaChanged(Meta::Title),
+        Event::MediaPlayerBuffering(0.5),
+        Event::VlmMediaAdded(None, Some("channel".to_owned())),
+    ];
+    let json = serde_json::to_string(&events).unwrap();
+    assert_eq!(json, concat!(
+        r#"[{"MediaStateChanged":"Playing"},{"MediaMetaChanged":"Title"},"#,
+        r#"{"MediaPlayerBuffering":0.5},{"VlmMediaAdded":{"instance_name":null,"media_name":"channel"}}]"#));
+
+    let back: Vec<Event> = serde_json::from_str(&json).unwrap();
+    assert_eq!(format!("{:?}", back), format!("{:?}", events));
+}
+
+#[test]
+fn track_field_names() {
+    let track = MediaTrack {
+        codec: 1, original_fourcc: 2, id: 0, track_type: TrackType::Audio,
+        profile: 0, level: 0, bitrate: 128000, language: Some("en".to_owned()), description: None,
+        type_specific_data: MediaTrackUnion::Audio(AudioTrack{ channels: 2, rate: 44100 }),
+    };
+    let value = serde_json::to_value(&track).unwrap();
+    assert_eq!(value["track_type"], "Audio");
+    assert_eq!(value["type_specific_data"]["Audio"]["rate"], 44100);
+    assert_eq!(serde_json::from_value::<MediaTrack>(value).unwrap(), track);
+}
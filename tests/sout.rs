@@ -0,0 +1,54 @@
+extern crate vlc;
+
+use vlc::{Sout, SoutModule, TranscodeOptions, StdOptions, DuplicateOptions, RtpOptions, HttpOptions,
+          BroadcastConfig};
+
+#[test]
+fn render() {
+    let sout = Sout::new()
+        .transcode(TranscodeOptions::new().vcodec("h264").vb(800).scale(0.5).acodec("mp4a").ab(128))
+        .std(StdOptions::new().access("file").mux("mp4").dst("/tmp/my video.mp4"));
+    assert_eq!(sout.to_string(),
+               r#"#transcode{vcodec=h264,vb=800,scale=0.5,acodec=mp4a,ab=128}:std{access=file,mux=mp4,dst="/tmp/my video.mp4"}"#);
+
+    let sout = Sout::new().duplicate(DuplicateOptions::new()
+        .dst(Sout::new().display())
+        .dst_select(Sout::new().rtp(RtpOptions::new().dst("239.0.0.1").port(5004).sdp("rtsp://:5554/live")), "video"));
+    assert_eq!(sout.to_string(),
+               r#"#duplicate{dst=display,dst=rtp{dst=239.0.0.1,port=5004,sdp=rtsp://:5554/live},select=video}"#);
+
+    let config = BroadcastConfig::new("channel").output(Sout::new().http(HttpOptions::new().mux("ts").dst(":8080")));
+    assert_eq!(config.get_output(), Some("#http{mux=ts,dst=:8080}"));
+    assert_eq!(Sout::new().to_string(), "");
+}
+
+#[test]
+fn parse() {
+    let sout: Sout = r#"#transcode{vcodec=h264,vb=800,fps=25,deinterlace}:std{access=file,mux=ts,dst="a \"b\".ts"}"#.parse().unwrap();
+    match sout.modules() {
+        [SoutModule::Transcode(transcode), SoutModule::Std(std)] => {
+            assert_eq!(transcode.vcodec.as_deref(), Some("h264"));
+            assert_eq!((transcode.vb, transcode.fps), (Some(800), Some(25.0)));
+            assert_eq!(transcode.extra, vec![("deinterlace".to_owned(), None)]);
+            assert_eq!(std.dst.as_deref(), Some("a \"b\".ts"));
+        },
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(sout.to_string().parse(), Ok(sout));
+
+    let sout: Sout = "duplicate{dst=\"transcode{acodec=mp3}:std{dst=a.mp3}\",dst=es{dst=out-$i.$c},select=audio}:smem".parse().unwrap();
+    match sout.modules() {
+        [SoutModule::Duplicate(duplicate), SoutModule::Other { name, options }] => {
+            assert_eq!(duplicate.dst.len(), 2);
+            assert_eq!(duplicate.dst[0].chain.modules().len(), 2);
+            assert_eq!(duplicate.dst[1].select.as_deref(), Some("audio"));
+            assert_eq!((name.as_str(), options.len()), ("smem", 0));
+        },
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(sout.to_string().parse(), Ok(sout));
+
+    assert!("#transcode{vb=fast}".parse::<Sout>().is_err());
+    assert!("#std{dst=a".parse::<Sout>().is_err());
+    assert!("#std{dst=a}:".parse::<Sout>().is_err());
+}
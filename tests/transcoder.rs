@@ -0,0 +1,72 @@
+extern crate vlc;
+
+use std::env;
+use std::fs;
+use vlc::{Instance, Media, Sout, StdOptions, TranscodeOptions, Transcoder, TranscodePreset, TranscodeError};
+
+// 2 seconds of a 440 Hz tone, 8 kHz mono.
+const TONE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tone.wav");
+
+#[test]
+fn presets() {
+    assert_eq!(TranscodePreset::H264Mp4.sout().to_string(),
+               "#transcode{vcodec=h264,vb=2000,acodec=mp4a,ab=160,channels=2,samplerate=48000}:std{mux=mp4}");
+    assert_eq!(TranscodePreset::Flac.sout().to_string(),
+               "#transcode{acodec=flac,channels=2,samplerate=44100}:std{mux=raw}");
+    assert_eq!(TranscodePreset::Flac.extension(), "flac");
+    assert_eq!(TranscodePreset::OpusWebm.extension(), "webm");
+    assert!(TranscodePreset::Mp3.is_audio_only() && !TranscodePreset::H264Mp4.is_audio_only());
+}
+
+#[test]
+fn sout() {
+    let instance = Instance::new().unwrap();
+    let md = Media::new_location(&instance, "file:///input.mkv").unwrap();
+
+    // The destination is added to the std module of the preset.
+    let transcoder = Transcoder::with_preset(&instance, &md, TranscodePreset::Mp3, "/tmp/my song.mp3");
+    assert_eq!(transcoder.sout().to_string(),
+               "#transcode{acodec=mp3,ab=192,channels=2,samplerate=44100}:std{access=file,mux=raw,dst=\"/tmp/my song.mp3\"}");
+
+    // A std module is appended after another module.
+    let transcoder = Transcoder::new(&instance, &md, Sout::new().transcode(TranscodeOptions::new().acodec("vorb")), "out.ogg");
+    assert_eq!(transcoder.sout().to_string(), "#transcode{acodec=vorb}:std{access=file,dst=out.ogg}");
+
+    // The access of the profile is kept, and its destination is replaced.
+    let profile = Sout::new().std(StdOptions::new().access("http").mux("ts").dst("old.ts"));
+    let transcoder = Transcoder::new(&instance, &md, profile, "new.ts");
+    assert_eq!(transcoder.sout().to_string(), "#std{access=http,mux=ts,dst=new.ts}");
+
+    let transcoder = Transcoder::new(&instance, &md, Sout::new(), "copy.mkv");
+    assert_eq!(transcoder.sout().to_string(), "#std{access=file,dst=copy.mkv}");
+}
+
+#[test]
+fn callback_media() {
+    let instance = Instance::new().unwrap();
+    let md = Media::from_reader(&instance, std::io::Cursor::new(Vec::new())).unwrap();
+    let transcoder = Transcoder::with_preset(&instance, &md, TranscodePreset::Flac, "out.flac");
+    assert_eq!(transcoder.run(), Err(TranscodeError::UnsupportedMedia));
+}
+
+// Needs libvlc with its plugins.
+#[test]
+#[ignore]
+fn transcode_fixture() {
+    let instance = Transcoder::instance().unwrap();
+    let output = env::temp_dir().join(format!("vlc-rs-transcoder-{}.flac", std::process::id()));
+    // A stale output is replaced.
+    fs::write(&output, b"stale").unwrap();
+
+    let md = Media::new_path(&instance, TONE).unwrap();
+    let mut handle = Transcoder::with_preset(&instance, &md, TranscodePreset::Flac, &output).start().unwrap();
+    let progress: Vec<_> = handle.by_ref().collect();
+    assert!(progress.windows(2).all(|p| p[0].position <= p[1].position));
+
+    let result = handle.wait().unwrap();
+    assert_eq!(result.path, output);
+    assert!(result.size > 5);
+    assert_eq!(&fs::read(&output).unwrap()[..4], b"fLaC");
+    assert!(md.applied_options().is_empty());
+    fs::remove_file(&output).unwrap();
+}
@@ -0,0 +1,248 @@
+extern crate vlc;
+
+use vlc::{Instance, Vlm, VlmError, VlmMediaInfo, VlmMediaType, VlmInstanceStatus, BroadcastConfig, VodConfig, VlmMonitor,
+          VlmBatch, VlmBatchItem, VlmControl};
+
+#[test]
+fn media_management() {
+    let instance = Instance::new().unwrap();
+    instance.add_broadcast("channel", "file:///dev/null", "#display", None, true, false).unwrap();
+
+    assert_eq!(instance.set_enabled("channel", false), Ok(()));
+    assert_eq!(instance.add_input("channel", "file:///dev/zero"), Ok(()));
+    assert_eq!(instance.change_media("channel", "file:///dev/null", "#display",
+                                     Some(vec!["sout-keep".to_owned()]), true, true), Ok(()));
+    assert_eq!(instance.del_media("channel"), Ok(()));
+}
+
+#[test]
+fn errors() {
+    let instance = Instance::new().unwrap();
+
+    assert!(matches!(instance.del_media("missing"), Err(VlmError::Failed(_))));
+    assert_eq!(instance.set_output("bad\0name", "#display"), Err(VlmError::InvalidArgument("name")));
+    assert_eq!(instance.seek_media("channel", 150.0), Err(VlmError::InvalidArgument("percentage")));
+}
+
+// Implements only the required methods.
+struct ShowOnly;
+
+impl Vlm for ShowOnly {
+    fn add_broadcast(&self, _: &str, _: &str, _: &str, _: Option<Vec<String>>, _: bool, _: bool) -> Result<(), VlmError> { Ok(()) }
+    fn add_vod(&self, _: &str, _: &str, _: &str, _: Option<Vec<String>>, _: bool) -> Result<(), VlmError> { Ok(()) }
+    fn play_media(&self, _: &str) -> Result<(), VlmError> { Ok(()) }
+    fn pause_media(&self, _: &str) -> Result<(), VlmError> { Ok(()) }
+    fn stop_media(&self, _: &str) -> Result<(), VlmError> { Ok(()) }
+    fn get_media_instance_position(&self, _: &str, _: i32) -> Result<f32, VlmError> { Ok(0.0) }
+    fn get_media_instance_length(&self, _: &str, _: i32) -> Result<i32, VlmError> { Ok(0) }
+    fn get_media_instance_time(&self, _: &str, _: i32) -> Result<i32, VlmError> { Ok(0) }
+    fn get_media_instance_rate(&self, _: &str, _: i32) -> Result<i32, VlmError> { Ok(0) }
+    fn show_media(&self, _: &str) -> Result<String, VlmError> { Ok(SHOW_CHANNEL.to_owned()) }
+    fn vlm_release(&self) {}
+}
+
+#[test]
+fn default_methods() {
+    assert_eq!(ShowOnly.del_media("channel"), Err(VlmError::Unsupported));
+    assert_eq!(ShowOnly.show_media_info("channel").unwrap()[0].name, "channel");
+    assert_eq!(ShowOnly.show_media_info(""), Err(VlmError::Unsupported));
+}
+
+// Output of libvlc_vlm_show_media for a broadcast. The option texts are not reported.
+const SHOW_CHANNEL: &str = r##"{
+	"name": "channel",
+	"type": "broadcast",
+	"enabled": "yes",
+	"loop": "no",
+	"inputs": [
+		"file:///a.mp4",
+		"file:///b "quoted".mp4" 
+	],
+	"output": "#std{access=http,mux=ts,dst=:8080}",
+	"options": [
+		null 
+	],
+	"instances": {
+		"name": "instances",
+		"instance": {
+			"name": "instance",
+			"name": "default",
+			"state": "playing",
+			"position": "0.250000",
+			"time": "15000000",
+			"length": "60000000",
+			"rate": "1.000000",
+			"title": "0",
+			"chapter": "0",
+			"can-seek": "1",
+			"playlistindex": "1" 
+		} 
+	} 
+}
+"##;
+
+#[test]
+fn parse_show_media() {
+    let channel = VlmMediaInfo::parse(SHOW_CHANNEL).unwrap();
+    assert_eq!(channel.name, "channel");
+    assert_eq!(channel.media_type, VlmMediaType::Broadcast);
+    assert!(channel.enabled && !channel.loop_broadcast);
+    assert_eq!(channel.inputs, vec!["file:///a.mp4", "file:///b \"quoted\".mp4"]);
+    assert_eq!(channel.output.as_deref(), Some("#std{access=http,mux=ts,dst=:8080}"));
+    assert_eq!(channel.options, vec![None]);
+
+    let instance = &channel.instances[0];
+    assert_eq!(instance.name, "default");
+    assert_eq!(instance.status, Some(VlmInstanceStatus::Playing));
+    assert_eq!((instance.position, instance.time, instance.length, instance.rate), (0.25, 15000, 60000, 1.0));
+
+    let vod = "{\n\t\"name\": \"movie\",\n\t\"type\": \"vod\",\n\t\"enabled\": \"no\",\n\t\"mux\": \"mp2t\",\n\t\"inputs\": null,\n\t\"output\": \"\",\n\t\"options\": null,\n\t\"instances\": null \n}\n";
+    let movie = VlmMediaInfo::parse(vod).unwrap();
+    assert_eq!((movie.name.as_str(), movie.media_type), ("movie", VlmMediaType::Vod));
+    assert_eq!(movie.mux.as_deref(), Some("mp2t"));
+    assert_eq!(movie.output, None);
+    assert!(movie.inputs.is_empty() && movie.options.is_empty() && movie.instances.is_empty());
+
+    // The output of an empty name, which only counts the media.
+    let count_only = "{\n\t\"media\": \"( 1 broadcast - 0 vod )\",\n\t\"schedule\": null \n}\n";
+    assert!(matches!(VlmMediaInfo::parse(count_only), Err(VlmError::Parse(_))));
+    assert!(matches!(VlmMediaInfo::parse("garbage"), Err(VlmError::Parse(_))));
+}
+
+#[test]
+fn media_names() {
+    let instance = Instance::new().unwrap();
+    instance.add_broadcast("first", "file:///dev/null", "#display", None, true, false).unwrap();
+    instance.add_vod("second", "file:///dev/null", "ts", None, true).unwrap();
+    assert_eq!(instance.media_names(), Ok(vec!["first".to_owned(), "second".to_owned()]));
+
+    instance.del_media("first").unwrap();
+    assert_eq!(instance.media_names(), Ok(vec!["second".to_owned()]));
+
+    // Media added without Vlm are listed from the VLM events.
+    let name = std::ffi::CString::new("raw").unwrap();
+    let null = std::ffi::CString::new("file:///dev/null").unwrap();
+    let display = std::ffi::CString::new("#display").unwrap();
+    assert_eq!(unsafe {
+        vlc::sys::libvlc_vlm_add_broadcast(instance.raw(), name.as_ptr(), null.as_ptr(), display.as_ptr(),
+                                           0, std::ptr::null(), 1, 0)
+    }, 0);
+    assert_eq!(instance.clone().media_names(), Ok(vec!["second".to_owned(), "raw".to_owned()]));
+
+    instance.vlm_release();
+    assert_eq!(instance.media_names(), Ok(vec![]));
+
+    // The names belong to the instance.
+    instance.add_broadcast("first", "file:///dev/null", "#display", None, true, false).unwrap();
+    drop(instance);
+    assert_eq!(Instance::new().unwrap().media_names(), Ok(vec![]));
+}
+
+#[test]
+fn config_validation() {
+    let instance = Instance::new().unwrap();
+
+    let no_input = BroadcastConfig::new("channel").output("#display");
+    assert_eq!(no_input.apply(&instance), Err(VlmError::InvalidArgument("inputs")));
+
+    let bad_name = BroadcastConfig::new("my channel").add_input("file:///a.mp4");
+    assert_eq!(bad_name.validate(), Err(VlmError::InvalidArgument("name")));
+
+    let bad_mux = VodConfig::new("movie").add_input("file:///movie.mkv").mux("");
+    assert_eq!(bad_mux.validate(), Err(VlmError::InvalidArgument("mux")));
+}
+
+#[test]
+fn config_apply() {
+    let instance = Instance::new().unwrap();
+
+    let channel = BroadcastConfig::new("channel")
+        .add_input("file:///a.mp4")
+        .add_input("file:///b.mp4")
+        .output("#std{access=http,mux=ts,dst=:8080}")
+        .option("sout-keep")
+        .loop_broadcast(true);
+    assert_eq!(channel.inputs().len(), 2);
+    assert!(channel.is_enabled() && channel.is_loop());
+    assert_eq!(channel.apply(&instance), Ok(()));
+    assert_eq!(channel.apply(&instance), Ok(()));
+
+    let movie = VodConfig::new("movie").add_input("file:///movie.mkv").mux("mp2t").enabled(false);
+    assert_eq!(movie.apply(&instance), Ok(()));
+    assert_eq!(instance.show_media_info("movie").unwrap()[0].mux.as_deref(), Some("mp2t"));
+
+    // Applying without a muxer resets the muxer of the existing VOD.
+    let movie = VodConfig::new("movie").add_input("file:///movie.mkv");
+    assert_eq!(movie.apply(&instance), Ok(()));
+    assert_eq!(instance.show_media_info("movie").unwrap()[0].mux, None);
+}
+
+#[test]
+fn batch_parse() {
+    let batch = VlmBatch::parse(r#"
+# channels
+new channel broadcast enabled loop
+  # don't start the "backup yet
+setup channel input file:///a.mp4
+setup channel input "file:///with space.mp4"
+setup channel output #std{access=http,mux=ts,dst=:8080}
+new movie vod enabled input 'file:///movie.mkv' mux mp2t
+control channel play
+control channel seek 50%
+"#).unwrap();
+
+    let channel = BroadcastConfig::new("channel")
+        .add_input("file:///a.mp4")
+        .add_input("file:///with space.mp4")
+        .output("#std{access=http,mux=ts,dst=:8080}")
+        .loop_broadcast(true);
+    let movie = VodConfig::new("movie").add_input("file:///movie.mkv").mux("mp2t");
+    assert_eq!(batch.items(), &[
+        VlmBatchItem::Broadcast(channel),
+        VlmBatchItem::Vod(movie),
+        VlmBatchItem::Control(VlmControl::Play("channel".to_owned())),
+        VlmBatchItem::Control(VlmControl::Seek("channel".to_owned(), 50.0)),
+    ]);
+    assert_eq!(VlmBatch::parse(&batch.to_string()), Ok(batch));
+
+    match VlmBatch::parse("new channel broadcast\nsetup other input a") {
+        Err(VlmError::Parse(msg)) => assert_eq!(msg, "line 2: unknown media other"),
+        other => panic!("{:?}", other),
+    }
+    assert!(VlmBatch::parse("new channel schedule").is_err());
+    assert!(VlmBatch::parse("new movie vod output #display").is_err());
+    assert!(VlmBatch::parse("setup channel input \"unterminated").is_err());
+}
+
+#[test]
+fn batch_export() {
+    let info = VlmMediaInfo::parse(SHOW_CHANNEL).unwrap();
+    let batch = VlmBatch::from_info(&[info]);
+    match batch.items() {
+        [VlmBatchItem::Broadcast(config), VlmBatchItem::Control(VlmControl::Play(name))] => {
+            assert_eq!(config.name(), "channel");
+            assert_eq!(name, "channel");
+        },
+        other => panic!("{:?}", other),
+    }
+    assert!(batch.to_string().contains("setup channel input \"file:///b \\\"quoted\\\".mp4\"\n"));
+    assert_eq!(VlmBatch::parse(&batch.to_string()), Ok(batch.clone()));
+
+    let instance = Instance::new().unwrap();
+    assert_eq!(batch.apply(&instance), Ok(()));
+
+    let instance = Instance::new().unwrap();
+    assert_eq!(VlmBatch::export(&instance, &[]), Ok(VlmBatch::new()));
+    assert_eq!(VlmBatch::export(&instance, &["missing"]), Err(VlmError::NotFound("missing".to_owned())));
+}
+
+#[test]
+fn monitor() {
+    let instance = Instance::new().unwrap();
+    let monitor = VlmMonitor::new(&instance).unwrap();
+    let _changes = monitor.subscribe();
+
+    assert!(monitor.media().is_empty());
+    assert_eq!(monitor.status("channel", "default"), None);
+    drop(monitor);
+}